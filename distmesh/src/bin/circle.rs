@@ -17,21 +17,25 @@ struct Model {
 impl Model {    
     fn update(&mut self) {
         self.distmesh.update(0.1);
+    }
+}
+
+struct QualityLogger;
 
-        println!("quality: {}", avg_quality(
-            &self.distmesh.points, 
-            &self.distmesh.triangulation.triangles)
-        );
+impl Observer for QualityLogger {
+    fn after_pushback(&mut self, view: &MeshView) -> std::ops::ControlFlow<()> {
+        println!("step {}, quality: {}", view.iteration + 1, avg_quality(view.points, &view.domain_triangles()));
+        std::ops::ControlFlow::Continue(())
     }
 }
 
 trait TraitPoint {
-    fn p2d(self: &Self) -> Point2;
+    fn p2d(&self) -> Point2;
 }
 
 impl TraitPoint for Point {
 
-    fn p2d(self: &Self) -> Point2 {
+    fn p2d(&self) -> Point2 {
         pt2(self.x as f32, self.y as f32)
     }
 }
@@ -51,6 +55,7 @@ fn model(app: &App) -> Model {
         .virtual_edges()
         .break_edges()
         .bosson()
+        .observer(Box::new(QualityLogger))
         .dist_fn(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 300.0)));
 
     let distmesh = builder.build();
@@ -58,7 +63,7 @@ fn model(app: &App) -> Model {
     Model { _window, distmesh}
 }
 
-fn update(app: &App, model: &mut Model, _update: Update) {
+fn update(_app: &App, model: &mut Model, _update: Update) {
     model.update();
 }
 
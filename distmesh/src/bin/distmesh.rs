@@ -26,8 +26,7 @@ fn main() {
     let mut distmesh = builder.build();
     println!("finish building distmesh");
 
-    for i in 0..niterations {
-        distmesh.update(DELTA_T);
-        println!("step {}, quality: {}", (i+1), avg_quality(&distmesh.points, &distmesh.triangulation.triangles));
-    }
+    let report = distmesh.run(&StopCriteria::new(niterations).dptol(0.001));
+    println!("stopped after {} steps ({:?}), quality: {}, min quality: {}", 
+        report.iterations, report.reason, report.avg_quality, report.min_quality);
 }
\ No newline at end of file
@@ -1,21 +1,25 @@
-use std::usize;
-
-use delaunator::{next_halfedge, prev_halfedge, triangulate, Point, Triangulation, EMPTY, EPSILON};
+use delaunator::{next_halfedge, prev_halfedge, triangulate, Point, Triangulation, EMPTY};
 use crate::{geometry::DSPoint, geometry::polygon_centroid, sfd::SignedDistanceFunction, Rect};
-use rand::random;
-
-pub type EdgeLenFn = fn(u: &Point) -> f64;
+use crate::quality::{avg_quality, min_quality};
+use crate::observer::{MeshView, Observer};
+use crate::size::SizeFunction;
+use crate::distribution::{distribute_points, Initializer};
+use rand::{rngs::StdRng, SeedableRng};
+use meshing::mesh::Mesh;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 
 const OMEGA: f64 = 1.2;
 const BREAK_POINT: f64 = 2.0;
 pub const DELTA_T: f64 = 0.15;
 pub const PUSH_BACK_EPS: f64 = 0.000001;
 
+#[derive(Clone)]
 pub struct BoundigBox {
-  x: f64,
-  y: f64,
-  w: f64,
-  h: f64,
+  pub(crate) x: f64,
+  pub(crate) y: f64,
+  pub(crate) w: f64,
+  pub(crate) h: f64,
 }
 
 impl BoundigBox {
@@ -33,11 +37,17 @@ pub struct DistMeshBuilder {
   x2: f64,
   y2: f64,
   fixpoints: Vec<Point>,
-  edge_len_fn: Option<EdgeLenFn>,
+  edge_len_fn: Box<dyn SizeFunction>,
   dist_fn: Option<Box<dyn SignedDistanceFunction>>,
   smoothing_fn: fn(labmda_k: f64) -> f64,
   use_virtual_edges: bool,
   break_edges: bool, 
+  use_mesh: bool,
+  ttol: f64,
+  observers: Vec<Box<dyn Observer>>,
+  seed: Option<u64>,
+  initializer: Initializer,
+  initial_points: Option<Vec<Point>>,
 }
 
 impl DistMeshBuilder {
@@ -50,11 +60,17 @@ impl DistMeshBuilder {
     DistMeshBuilder {
       npoints, x1, y1, x2, y2, 
       fixpoints: Vec::new(), 
-      edge_len_fn: Some(|_: &Point| {1.0}), 
+      edge_len_fn: Box::new(1.0),
       dist_fn: None,
       smoothing_fn: bosson,
       use_virtual_edges: false,
       break_edges: false,
+      use_mesh: false,
+      ttol: 0.0,
+      observers: Vec::new(),
+      seed: None,
+      initializer: Initializer::Random,
+      initial_points: None,
     }
   }
 
@@ -83,6 +99,53 @@ impl DistMeshBuilder {
     self
   }
 
+  /// Keeps the connectivity of a half-edge [`Mesh`] between updates and restores the Delaunay property by local edge flips
+  /// instead of triangulating all points from scratch. Only if an inner triangle becomes inverted, the points are triangulated again.
+  /// The forces are computed on the mesh itself and [`DistMesh::triangulation`] only contains its triangles and hull but no linked halfedges.
+  pub fn half_edge_mesh(mut self) -> Self {
+    self.use_mesh = true;
+    self
+  }
+
+  /// Sets the retriangulation tolerance, i.e. the points are triangulated again only if one of them moved farther than
+  /// ```ttol``` times the scaled edge length since the last triangulation. The default is 0, i.e. the points are triangulated in each update.
+  pub fn ttol(mut self, ttol: f64) -> Self {
+    assert!(ttol >= 0.0);
+    self.ttol = ttol;
+    self
+  }
+
+  /// Seeds the random number generator used to distribute the points such that the generated mesh is reproducible.
+  /// Without a seed, the generator is seeded by the operating system.
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.seed = Some(seed);
+    self
+  }
+
+  /// Selects how the points are placed initially, see [`Initializer`].
+  pub fn initializer(mut self, initializer: Initializer) -> Self {
+    self.initializer = initializer;
+    self
+  }
+
+  /// Starts from the vertices of ```mesh```, e.g. a mesh produced by a [`meshing::refinement::Refinement`], instead of distributing
+  /// new points. The number of points and the [`Initializer`] are ignored in this case. Vertices which are also added as fixpoints become fixed.
+  pub fn initial_mesh(mut self, mesh: &Mesh) -> Self {
+    self.initial_points = Some(mesh.iter_vertices()
+      .filter(|&v| mesh.edge_of_vertex(v) != meshing::mesh::EMPTY)
+      .map(|v| {
+        let p = mesh.point_of_vertex(v);
+        Point {x: p.x, y: p.y}
+      })
+      .collect());
+    self
+  }
+
+  pub fn observer(mut self, observer: Box<dyn Observer>) -> Self {
+    self.observers.push(observer);
+    self
+  }
+
   pub fn x1(mut self, x1: f64) -> Self {
     self.x1 = x1;
     self
@@ -108,8 +171,9 @@ impl DistMeshBuilder {
     self
   }
 
-  pub fn edge_len_fn(mut self, edge_len_fn: EdgeLenFn) -> Self {
-    self.edge_len_fn = Some(edge_len_fn);
+  /// Sets the desired (relative) edge length, see [`SizeFunction`]. The default is a uniform edge length.
+  pub fn edge_len_fn<S: SizeFunction + 'static>(mut self, edge_len_fn: S) -> Self {
+    self.edge_len_fn = Box::new(edge_len_fn);
     self
   }
 
@@ -119,14 +183,16 @@ impl DistMeshBuilder {
     assert!(self.y1 < self.y2);
 
     let bbox = BoundigBox {x: self.x1, y: self.y1, w: self.x2-self.x1, h: self.y2-self.y1};
-    let dist_fn: Box<dyn SignedDistanceFunction> = self.dist_fn.or(Some(Box::new(bbox.to_rect()))).unwrap();
+    let dist_fn: Box<dyn SignedDistanceFunction> = self.dist_fn.unwrap_or(Box::new(bbox.to_rect()));
     
-    let mut points: Vec<Point> = distribute_points(self.npoints, &bbox, &dist_fn);
+    let mut rng = create_rng(self.seed);
+    let mut points: Vec<Point> = match self.initial_points {
+      // vertices of the initial mesh which are fixpoints are added as fixpoints below
+      Some(points) => points.into_iter().filter(|p| !self.fixpoints.contains(p)).collect(),
+      None => distribute_points(self.npoints, self.initializer, &bbox, dist_fn.as_ref(), self.edge_len_fn.as_ref(), &mut rng),
+    };
     
-    let mut fixpoints: Vec<bool> = Vec::with_capacity(self.fixpoints.len() + points.len());
-    for _ in &points {
-      fixpoints.push(false);
-    }
+    let mut fixpoints: Vec<bool> = vec![false; points.len()];
     
     for point in self.fixpoints {
       points.push(point);
@@ -134,73 +200,209 @@ impl DistMeshBuilder {
     }
 
     let triangulation = triangulate(&points);
-
-    //let d: EdgeLenFn = |p: &Point| {1.0 + Rect::new(Point {x: 0.0, y: 0.0}, 500.0, 500.0).distance(p).abs()/500.0};
-
-    DistMesh {
-      points: points, 
-      triangulation: triangulation, 
-      edge_len_fn: self.edge_len_fn.expect("expect valid edge length function"),
-      //edge_len_fn: d, 
-      dist_fn: dist_fn,
+    let mesh = if self.use_mesh { Some(Mesh::from_triangulation(&points, &triangulation)) } else { None };
+
+    let mut distmesh = DistMesh {
+      last_points: points.clone(),
+      points, 
+      full_triangulation: triangulation.clone(),
+      triangulation, 
+      edge_len_fn: self.edge_len_fn,
+      dist_fn,
       smoothing_fn: self.smoothing_fn,
       use_virtual_edges: self.use_virtual_edges,
       break_edges: self.break_edges,
-      fixpoints: fixpoints,
+      fixpoints,
+      mesh,
+      ttol: self.ttol,
+      scale: 0.0,
+      retriangulations: 0,
+      max_displacement: f64::INFINITY,
+      observers: self.observers,
+      aborted: false,
       update_counter: 0,
+    };
+    // the tolerance ttol is relative to the scaled edge length which is therefore required before the first update
+    distmesh.scale = distmesh.compute_scaling();
+    distmesh
+  }
+}
+
+/// Conditions under which [`DistMesh::run`] stops. Apart from the maximum number of iterations all of them are optional.
+pub struct StopCriteria {
+  max_iterations: usize,
+  delta: f64,
+  dptol: Option<f64>,
+  avg_quality: Option<f64>,
+  min_quality: Option<f64>,
+  max_duration: Option<Duration>,
+}
+
+impl StopCriteria {
+  pub fn new(max_iterations: usize) -> Self {
+    StopCriteria {
+      max_iterations,
+      delta: DELTA_T,
+      dptol: None,
+      avg_quality: None,
+      min_quality: None,
+      max_duration: None,
     }
   }
+
+  /// Sets the time step of each update, the default is [`DELTA_T`].
+  pub fn delta(mut self, delta: f64) -> Self {
+    self.delta = delta;
+    self
+  }
+
+  /// Stops if no point moved farther than ```dptol``` times the scaled edge length during an update, see [`DistMesh::max_displacement`].
+  pub fn dptol(mut self, dptol: f64) -> Self {
+    self.dptol = Some(dptol);
+    self
+  }
+
+  /// Stops if the average quality of the triangles reaches ```quality```, see [`avg_quality`].
+  pub fn avg_quality(mut self, quality: f64) -> Self {
+    self.avg_quality = Some(quality);
+    self
+  }
+
+  /// Stops if the quality of the worst triangle reaches ```quality```, see [`min_quality`].
+  pub fn min_quality(mut self, quality: f64) -> Self {
+    self.min_quality = Some(quality);
+    self
+  }
+
+  /// Stops if the run takes longer than ```duration```. The running update is always finished.
+  pub fn max_duration(mut self, duration: Duration) -> Self {
+    self.max_duration = Some(duration);
+    self
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+  MaxIterations,
+  /// the maximum displacement fell below ```dptol```
+  Converged,
+  AvgQuality,
+  MinQuality,
+  Timeout,
+  /// an [`Observer`] aborted the update
+  Aborted,
+}
+
+/// Summary of [`DistMesh::run`] containing the reason why it stopped and the final statistics.
+#[derive(Debug, Clone)]
+pub struct RunReport {
+  pub reason: StopReason,
+  pub iterations: usize,
+  pub duration: Duration,
+  pub avg_quality: f64,
+  pub min_quality: f64,
+  pub max_displacement: f64,
+  pub retriangulations: usize,
 }
 
 pub struct DistMesh {
   pub points: Vec<Point>,
   pub triangulation: Triangulation,
-  edge_len_fn: EdgeLenFn,
+  /// triangulation of all points including triangles outside of the domain, with the half-edge mesh it is only derived for observers
+  full_triangulation: Triangulation,
+  /// positions of the points at the last triangulation
+  last_points: Vec<Point>,
+  edge_len_fn: Box<dyn SizeFunction>,
   dist_fn: Box<dyn SignedDistanceFunction>,
   smoothing_fn: fn(lambda_k: f64) -> f64,
   use_virtual_edges: bool,
   break_edges: bool,
   fixpoints: Vec<bool>,
+  mesh: Option<Mesh>,
+  ttol: f64,
+  scale: f64,
+  retriangulations: usize,
+  max_displacement: f64,
+  observers: Vec<Box<dyn Observer>>,
+  aborted: bool,
   update_counter: usize,
 }
 
 impl DistMesh {
 
+  /// Creates a new mesh of ```npoints``` points randomly distributed inside the domain.
   pub fn new(npoints: usize, bouding_box: BoundigBox, dist_fn: Box<dyn SignedDistanceFunction>) -> Self {
-    let points: Vec<Point> = distribute_points(npoints, &bouding_box, &dist_fn);
-    let edge_len_fn = |_: &Point| {1.0};
+    Self::create(npoints, bouding_box, dist_fn, None)
+  }
+
+  /// Same as [`DistMesh::new`] but seeds the random number generator such that the generated mesh is reproducible.
+  pub fn with_seed(npoints: usize, bouding_box: BoundigBox, dist_fn: Box<dyn SignedDistanceFunction>, seed: u64) -> Self {
+    Self::create(npoints, bouding_box, dist_fn, Some(seed))
+  }
+
+  fn create(npoints: usize, bouding_box: BoundigBox, dist_fn: Box<dyn SignedDistanceFunction>, seed: Option<u64>) -> Self {
+    let edge_len_fn: Box<dyn SizeFunction> = Box::new(1.0);
+    let points: Vec<Point> = distribute_points(npoints, Initializer::Random, &bouding_box, dist_fn.as_ref(), edge_len_fn.as_ref(), &mut create_rng(seed));
     let triangulation = triangulate(&points);
     DistMesh{ 
-      points: points, triangulation, 
+      last_points: points.clone(),
+      full_triangulation: triangulation.clone(),
+      points, triangulation, 
       edge_len_fn, dist_fn, 
       smoothing_fn: bosson,
       use_virtual_edges: false,
       break_edges: false,
       fixpoints: Vec::new(), 
+      mesh: None,
+      ttol: 0.0,
+      scale: 0.0,
+      retriangulations: 0,
+      max_displacement: f64::INFINITY,
+      observers: Vec::new(),
+      aborted: false,
       update_counter: 0}
   }
 
   pub fn update(&mut self, delta: f64) {
+    self.aborted = false;
+
     // 1. compute scale value
-    self.triangulation = triangulate(&self.points);
+    if self.requires_retriangulation() {
+      self.retriangulate();
+      if self.notify(|observer, view| observer.after_retriangulation(view), None) {
+        return;
+      }
+    }
     let scale = self.compute_scaling();
+    self.scale = scale;
 
     if self.break_edges {
       self.break_edges(scale);
     }
 
+    if self.notify(|observer, view| observer.before_forces(view), None) {
+      return;
+    }
+
     // 2. compute forces
     let forces = self.compute_forces(scale);
+    if self.notify(|observer, view| observer.after_forces(view), Some(&forces)) {
+      return;
+    }
 
     // 3. update forces
+    let previous_points = self.points.clone();
     self.update_points(&forces, delta);
 
     // 4. push back
     self.pushback_points();
+    self.max_displacement = self.points.iter().zip(previous_points.iter())
+      .map(|(p, q)| p.distance(q) / scale)
+      .fold(0.0, f64::max);
+    self.notify(|observer, view| observer.after_pushback(view), Some(&forces));
 
     // 5. trangulate
     //if self.update_counter % 20 == 0 {
-      //self.collapse_edges(scale);
       
       self.remove_triangles();
     //}
@@ -208,76 +410,184 @@ impl DistMesh {
     self.update_counter += 1;
   }
 
-  fn remove_triangles(&mut self) {
-    let mut filtered_triangles: Vec<usize> = Vec::new();
-    for i in 0..self.triangulation.len() {
-      let iu = self.triangulation.triangles[3*i];
-      let iv = self.triangulation.triangles[3*i+1];
-      let iw = self.triangulation.triangles[3*i+2];
+  /// Calls [`DistMesh::update`] until one of the ```criteria``` is met and reports why it stopped.
+  pub fn run(&mut self, criteria: &StopCriteria) -> RunReport {
+    let start = Instant::now();
+    let mut iterations = 0;
+    let reason = loop {
+      if iterations >= criteria.max_iterations {
+        break StopReason::MaxIterations;
+      }
 
-      let u: &Point = &self.points[iu];
-      let v: &Point = &self.points[iv];
-      let w: &Point = &self.points[iw];
+      self.update(criteria.delta);
+      iterations += 1;
+
+      if self.aborted {
+        break StopReason::Aborted;
+      }
+
+      if criteria.dptol.is_some_and(|dptol| self.max_displacement < dptol) {
+        break StopReason::Converged;
+      }
+
+      if criteria.avg_quality.is_some_and(|quality| avg_quality(&self.points, &self.triangulation.triangles) >= quality) {
+        break StopReason::AvgQuality;
+      }
 
-      let opt_centroid = polygon_centroid(&[u, v, w]);
-      if opt_centroid.is_some() && self.dist_fn.distance(&opt_centroid.unwrap()) < 0.0 {
-        filtered_triangles.push(iu);
-        filtered_triangles.push(iv);
-        filtered_triangles.push(iw);
+      if criteria.min_quality.is_some_and(|quality| min_quality(&self.points, &self.triangulation.triangles) >= quality) {
+        break StopReason::MinQuality;
       }
+
+      if criteria.max_duration.is_some_and(|duration| start.elapsed() >= duration) {
+        break StopReason::Timeout;
+      }
+    };
+
+    RunReport {
+      reason,
+      iterations,
+      duration: start.elapsed(),
+      avg_quality: avg_quality(&self.points, &self.triangulation.triangles),
+      min_quality: min_quality(&self.points, &self.triangulation.triangles),
+      max_displacement: self.max_displacement,
+      retriangulations: self.retriangulations,
     }
-    self.triangulation.triangles = filtered_triangles;
   }
 
-  fn collapse_edges(&mut self, scale: f64) {
-    let len = self.triangulation.hull.len();
-    let mut removes: Vec<usize> = Vec::new();
-    for i in 0..len {
-      let iu = self.triangulation.hull[i];
-      let iv = self.triangulation.hull[(i + 1) % len];
-      let iw = self.triangulation.hull[(i + 2) % len];
+  pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+    self.observers.push(observer);
+  }
 
-      let u: &Point = &self.points[iu];
-      let v: &Point = &self.points[iv];
-      let w: &Point = &self.points[iw];
+  /// Returns **true** if and only if an [`Observer`] aborted the last update.
+  pub fn is_aborted(&self) -> bool {
+    self.aborted
+  }
 
-      let uv: Point = u.subtract(v);
-      let vw: Point = v.subtract(w);
-      let uv_center = u.center(v);
-      let vw_center = v.center(w);
+  /// Calls ```hook``` for all observers and returns **true** if one of them aborted the update.
+  fn notify(&mut self, hook: fn(&mut dyn Observer, &MeshView) -> ControlFlow<()>, forces: Option<&[Point]>) -> bool {
+    if self.observers.is_empty() {
+      return false;
+    }
+
+    if let Some(mesh) = self.mesh.as_ref() {
+      self.full_triangulation = mesh.to_triangulation();
+    }
+
+    let mut observers = std::mem::take(&mut self.observers);
+    let view = MeshView {
+      iteration: self.update_counter,
+      points: &self.points,
+      triangulation: &self.full_triangulation,
+      forces,
+      dist_fn: self.dist_fn.as_ref(),
+    };
+    for observer in observers.iter_mut() {
+      if hook(observer.as_mut(), &view).is_break() {
+        self.aborted = true;
+      }
+    }
+    self.observers = observers;
+    self.aborted
+  }
+
+  /// Returns the largest distance a point moved during the last update relative to the scaled edge length.
+  pub fn max_displacement(&self) -> f64 {
+    self.max_displacement
+  }
+
+  /// Returns how many times the points have been triangulated during the updates, see [`DistMeshBuilder::ttol`].
+  pub fn number_of_retriangulations(&self) -> usize {
+    self.retriangulations
+  }
+
+  /// Returns **true** if and only if points were added or some point moved farther than ```ttol``` times the scaled edge length
+  /// since the last triangulation.
+  fn requires_retriangulation(&self) -> bool {
+    if self.points.len() != self.last_points.len() {
+      return true;
+    }
+
+    let max_dist_sq = (self.ttol * self.scale).powi(2);
+    self.points.iter().zip(self.last_points.iter()).any(|(p, q)| p.distance_sq(q) > max_dist_sq)
+  }
 
-      let h_k1: f64 = (self.edge_len_fn)(&uv_center) * scale;
-      let h_k2: f64 = (self.edge_len_fn)(&vw_center) * scale;
+  /// Computes the Delaunay triangulation of the current points, either from scratch or, if the half-edge mesh is used,
+  /// by moving its vertices and flipping illegal edges. The mesh is rebuilt only if one of its inner triangles became inverted.
+  fn retriangulate(&mut self) {
+    self.retriangulations += 1;
+    self.last_points.clone_from(&self.points);
 
-      if uv.len() / h_k1 < 0.3 && vw.len() / h_k2 < 0.3 {
-        removes.push(iv);
+    if let Some(mesh) = self.mesh.as_mut() {
+      for (v, p) in self.points.iter().enumerate() {
+        mesh.move_vertex(v, meshing::geometry::Point {x: p.x, y: p.y});
+      }
+
+      // inverted slivers along the hull are cut off and refilled, any other inversion requires a new triangulation
+      let inverted = mesh.inverted_faces();
+      if inverted.iter().all(|&face| mesh.remove_border_face(face)) {
+        if !inverted.is_empty() {
+          // no vertex is removed, thus, the vertex ids keep matching the indices of the points
+          mesh.compact();
+        }
+        mesh.convexify();
+        mesh.make_delaunay();
+        return;
       }
     }
 
-    removes.sort();
-    removes.reverse();
-    for i in 0..removes.len() {
-      self.points.remove(i);
-      self.fixpoints.remove(i);
+    self.full_triangulation = triangulate(&self.points);
+    if let Some(mesh) = self.mesh.as_mut() {
+      *mesh = Mesh::from_triangulation(&self.points, &self.full_triangulation);
     }
+  }
 
+  fn remove_triangles(&mut self) {
+    self.triangulation = match self.mesh.as_ref() {
+      // the halfedges of the remaining triangles are not linked
+      Some(mesh) => {
+        let mut triangles = mesh.to_triangles();
+        // use the orientation of delaunator
+        triangles.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2));
+        let triangles = filter_triangles(&self.points, &triangles, self.dist_fn.as_ref());
+        Triangulation {
+          halfedges: vec![EMPTY; triangles.len()],
+          triangles,
+          hull: mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.vertex(halfedge)).collect(),
+        }
+      },
+      None => Triangulation {
+        triangles: filter_triangles(&self.points, &self.full_triangulation.triangles, self.dist_fn.as_ref()),
+        halfedges: self.full_triangulation.halfedges.clone(),
+        hull: self.full_triangulation.hull.clone(),
+      },
+    };
   }
 
   fn break_edges(&mut self, scale: f64) {
-    let len = self.triangulation.hull.len();
-    for i in 0..len {
-      let iv = self.triangulation.hull[i];
-      let iu = self.triangulation.hull[(i + 1) % len];
+    let hull: Vec<(usize, usize)> = match self.mesh.as_ref() {
+      Some(mesh) => mesh.iter_face(mesh.boundary()).map(|halfedge| (mesh.vertex(mesh.twin(halfedge)), mesh.vertex(halfedge))).collect(),
+      None => {
+        let hull = &self.full_triangulation.hull;
+        (0..hull.len()).map(|i| (hull[i], hull[(i + 1) % hull.len()])).collect()
+      },
+    };
 
+    for (iv, iu) in hull {
       let u: &Point = &self.points[iu];
       let v: &Point = &self.points[iv];
       let uv: Point = u.subtract(v);
       let center = u.center(v);
 
-      let h_k: f64 = (self.edge_len_fn)(&center) * scale;
+      let h_k: f64 = self.edge_len_fn.size(&center) * scale;
       let lambda_k: f64 = uv.len() / h_k;
       if lambda_k > BREAK_POINT {
         //center.add_mut(&Point {x: EPSILON, y: EPSILON});
+        if let Some(mesh) = self.mesh.as_mut() {
+          let halfedge = mesh.iter_face(mesh.boundary())
+            .find(|&halfedge| mesh.vertex(halfedge) == iu && mesh.vertex(mesh.twin(halfedge)) == iv)
+            .expect("expect hull edge on the boundary");
+          mesh.split_edge(halfedge, meshing::geometry::Point {x: center.x, y: center.y});
+        }
         self.points.push(center);
         self.fixpoints.push(false);
       }
@@ -289,23 +599,21 @@ impl DistMesh {
   }
 
   fn pushback_points(&mut self) {
-    let mut count = 0;
     for iu in 0..self.points.len() {
       if !self.is_fixpoint(iu) {
         let dist = self.dist_fn.distance(&self.points[iu]);
         if dist > 0.0 {
           let grad = self.dist_fn.grad_with_eps(&self.points[iu], PUSH_BACK_EPS);
           self.points[iu].subtract_mut(&grad.mult(dist));
-          count += 1;
         }
       } 
     }
   }
 
-  fn update_points(&mut self, forces: &Vec<Point>, delta: f64) {
-    for iu in 0..forces.len() {
+  fn update_points(&mut self, forces: &[Point], delta: f64) {
+    for (iu, force) in forces.iter().enumerate() {
       if !self.is_fixpoint(iu) {
-        self.points[iu].add_mut(&forces[iu].mult(delta));
+        self.points[iu].add_mut(&force.mult(delta));
       }
     }
   }
@@ -315,7 +623,7 @@ impl DistMesh {
   
     let center = u.center(v);
     let len_sq = dir.len_sq();
-    let h = (self.edge_len_fn)(&center);
+    let h = self.edge_len_fn.size(&center);
     (len_sq, h*h)
   }
   
@@ -324,24 +632,27 @@ impl DistMesh {
     let normed_dir = uv.norm();
     let center = u.center(v);
 
-    let h_k: f64 = (self.edge_len_fn)(&center) * OMEGA * scale;
+    let h_k: f64 = self.edge_len_fn.size(&center) * OMEGA * scale;
     let lambda_k: f64 = uv.len() / h_k;
     let nu_hat: f64 = (self.smoothing_fn)(lambda_k);
     let nu: f64 = nu_hat * h_k;
     
-    let force = normed_dir.mult(nu);
-    force
+    normed_dir.mult(nu)
   }
 
   fn compute_scaling(&self) -> f64 {
+    if let Some(mesh) = self.mesh.as_ref() {
+      return self.compute_scaling_on_mesh(mesh);
+    }
+
     let mut sum_h_sq = 0.0;
     let mut sum_len_sq = 0.0;
 
-    for &iedge in &self.triangulation.halfedges {
+    for &iedge in &self.full_triangulation.halfedges {
       if iedge != EMPTY {
-        let iu = self.triangulation.triangles[iedge];
-        let itwin = self.triangulation.halfedges[iedge];
-        let iv = self.triangulation.triangles[itwin];
+        let iu = self.full_triangulation.triangles[iedge];
+        let itwin = self.full_triangulation.halfedges[iedge];
+        let iv = self.full_triangulation.triangles[itwin];
 
         let u: &Point = &self.points[iu];
         let v: &Point = &self.points[iv];
@@ -352,17 +663,15 @@ impl DistMesh {
       }
     }
 
-    let scale = (sum_len_sq / sum_h_sq).sqrt();
-
-    let len = self.triangulation.hull.len();
+    let len = self.full_triangulation.hull.len();
 
     if len < 2 {
-      return scale;
+      return (sum_len_sq / sum_h_sq).sqrt();
     }
 
     for i in 0..len {
-      let iv = self.triangulation.hull[i];
-      let iu = self.triangulation.hull[(i + 1) % len];
+      let iv = self.full_triangulation.hull[i];
+      let iu = self.full_triangulation.hull[(i + 1) % len];
 
       let u: &Point = &self.points[iu];
       let v: &Point = &self.points[iv];
@@ -377,20 +686,45 @@ impl DistMesh {
       sum_len_sq += len_sq;
     }
 
-    scale
+    (sum_len_sq / sum_h_sq).sqrt()
+  }
+
+  /// Same as [`DistMesh::compute_scaling`] but for the edges of the half-edge ```mesh```. Each inner edge is visited from both sides.
+  fn compute_scaling_on_mesh(&self, mesh: &Mesh) -> f64 {
+    let mut sum_h_sq = 0.0;
+    let mut sum_len_sq = 0.0;
+
+    for face in mesh.iter_faces() {
+      for halfedge in mesh.iter_face(face) {
+        let u: &Point = &self.points[mesh.vertex(mesh.twin(halfedge))];
+        let v: &Point = &self.points[mesh.vertex(halfedge)];
+        let (len_sq, h_sq) = self.compute_ratio(u, v);
+        // edges of the hull are visited only once
+        let weight = if mesh.is_border(mesh.twin(halfedge)) { 2.0 } else { 1.0 };
+
+        sum_h_sq += weight * h_sq;
+        sum_len_sq += weight * len_sq;
+      }
+    }
+
+    (sum_len_sq / sum_h_sq).sqrt()
   }
 
   fn compute_forces(&self, scale: f64) -> Vec<Point> {
+    if let Some(mesh) = self.mesh.as_ref() {
+      return self.compute_forces_on_mesh(mesh, scale);
+    }
+
     let mut forces: Vec<Point> = Vec::with_capacity(self.points.len());
     for _ in 0..self.points.len() {
       forces.push(Point {x: 0.0, y: 0.0});
     }
     
-    for &iedge in &self.triangulation.halfedges {
+    for &iedge in &self.full_triangulation.halfedges {
       if iedge != EMPTY {
-        let iu = self.triangulation.triangles[iedge];
-        let itwin = self.triangulation.halfedges[iedge];
-        let iv = self.triangulation.triangles[itwin];
+        let iu = self.full_triangulation.triangles[iedge];
+        let itwin = self.full_triangulation.halfedges[iedge];
+        let iv = self.full_triangulation.triangles[itwin];
 
         let u: &Point = &self.points[iu];
         let v: &Point = &self.points[iv];
@@ -398,9 +732,9 @@ impl DistMesh {
         // add virtual force
         if self.use_virtual_edges {
           let inext = next_halfedge(iedge);
-          if self.triangulation.halfedges[inext] == EMPTY {
+          if self.full_triangulation.halfedges[inext] == EMPTY {
             let iprev: usize = prev_halfedge(iedge);
-            let iw =  self.triangulation.triangles[iprev];
+            let iw =  self.full_triangulation.triangles[iprev];
             let w: &Point = &self.points[iw];
             let virtual_ppoint = v.center(w);
             forces[iu].add_mut(&self.compute_force(u, &virtual_ppoint, scale * f64::sqrt(3.0)/2.0));
@@ -410,15 +744,15 @@ impl DistMesh {
         forces[iu].add_mut(&self.compute_force(u, v, scale));
       }
     }
-    let len = self.triangulation.hull.len();
+    let len = self.full_triangulation.hull.len();
 
     if len < 2 {
       return forces;
     }
 
     for i in 0..len {
-      let iv = self.triangulation.hull[i];
-      let iu = self.triangulation.hull[(i + 1) % len];
+      let iv = self.full_triangulation.hull[i];
+      let iu = self.full_triangulation.hull[(i + 1) % len];
 
       let u: &Point = &self.points[iu];
       let v: &Point = &self.points[iv];
@@ -430,33 +764,68 @@ impl DistMesh {
     forces
   }
 
+  /// Same as [`DistMesh::compute_forces`] but for the edges of the half-edge ```mesh```.
+  fn compute_forces_on_mesh(&self, mesh: &Mesh, scale: f64) -> Vec<Point> {
+    let mut forces: Vec<Point> = vec![Point {x: 0.0, y: 0.0}; self.points.len()];
+
+    for face in mesh.iter_faces() {
+      for halfedge in mesh.iter_face(face) {
+        let iu = mesh.vertex(mesh.twin(halfedge));
+        let iv = mesh.vertex(halfedge);
+        let u: &Point = &self.points[iu];
+        let v: &Point = &self.points[iv];
+        forces[iu].add_mut(&self.compute_force(u, v, scale));
+
+        // edges of the hull are visited only once
+        if mesh.is_border(mesh.twin(halfedge)) {
+          forces[iv].add_mut(&self.compute_force(v, u, scale));
+
+          // add virtual force acting on the opposite vertex if it is connected to iv by an inner edge
+          let next = mesh.next(halfedge);
+          if self.use_virtual_edges && !mesh.is_border(mesh.twin(next)) {
+            let iw = mesh.vertex(next);
+            let virtual_ppoint = u.center(v);
+            forces[iw].add_mut(&self.compute_force(&self.points[iw], &virtual_ppoint, scale * f64::sqrt(3.0)/2.0));
+          }
+        }
+      }
+    }
+
+    forces
+  }
+
   pub fn is_empty(&self, halfedge: usize) -> bool {
     halfedge == EMPTY
   }
 }
 
-fn distribute_points(n: usize, bouding_box: &BoundigBox, dist_fn: &Box<dyn SignedDistanceFunction>) -> Vec<Point> {
-  let mut points: Vec<Point>= Vec::with_capacity(n);
-  let mut count = 0;
-
-  while count < n {
-    let candidate = Point { 
-      x: bouding_box.x + bouding_box.w/2.0 + random_range(-bouding_box.w/2.0, bouding_box.w/2.0), 
-      y: bouding_box.y + bouding_box.h/2.0 + random_range(-bouding_box.h/2.0, bouding_box.h/2.0)
-    };
-
-    if dist_fn.distance(&candidate) < 0.0 {
-      points.push(candidate);
-      count += 1;
+/// Returns all ```triangles``` whose centroid lies inside the domain of ```dist_fn```.
+pub(crate) fn filter_triangles(points: &[Point], triangles: &[usize], dist_fn: &dyn SignedDistanceFunction) -> Vec<usize> {
+  let mut filtered_triangles: Vec<usize> = Vec::new();
+  for i in 0..triangles.len() / 3 {
+    let iu = triangles[3*i];
+    let iv = triangles[3*i+1];
+    let iw = triangles[3*i+2];
+
+    let u: &Point = &points[iu];
+    let v: &Point = &points[iv];
+    let w: &Point = &points[iw];
+
+    let opt_centroid = polygon_centroid(&[u, v, w]);
+    if opt_centroid.is_some() && dist_fn.distance(&opt_centroid.unwrap()) < 0.0 {
+      filtered_triangles.push(iu);
+      filtered_triangles.push(iv);
+      filtered_triangles.push(iw);
     }
   }
-
-  points
+  filtered_triangles
 }
 
-fn random_range(a: f64, b: f64) -> f64 {
-  let d = b-a;
-  a + random::<f64>()*d
+pub(crate) fn create_rng(seed: Option<u64>) -> StdRng {
+  match seed {
+    Some(seed) => StdRng::seed_from_u64(seed),
+    None => StdRng::from_entropy(),
+  }
 }
 
 pub fn bosson(lambda_k: f64) -> f64 {
@@ -465,4 +834,264 @@ pub fn bosson(lambda_k: f64) -> f64 {
 
 pub fn persson(lambda_k: f64) -> f64 {
   f64::max(1.0 - lambda_k, 0.0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{BoundaryGrading, Circle, Rect, DistanceGrading, quality::avg_quality};
+  use std::{cell::RefCell, rc::Rc};
+
+  fn circle() -> DistMeshBuilder {
+    DistMeshBuilder::new(300)
+      .x1(-1.0).x2(1.0)
+      .y1(-1.0).y2(1.0)
+      .dist_fn(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0)))
+  }
+
+  #[test]
+  fn test_half_edge_mesh() {
+    for builder in [circle().half_edge_mesh(), circle().half_edge_mesh().virtual_edges().break_edges()] {
+      let mut distmesh = builder.build();
+      for _ in 0..200 {
+        distmesh.update(DELTA_T);
+      }
+      let mesh = distmesh.mesh.as_ref().unwrap();
+      assert_eq!(mesh.check_delaunay(), Ok(()));
+      assert_eq!(mesh.number_of_vertices(), distmesh.points.len());
+      // destroyed slivers do not pile up
+      assert_eq!(mesh.number_of_faces(), mesh.iter_faces().count() + 1);
+      assert!(avg_quality(&distmesh.points, &distmesh.triangulation.triangles) > 0.9);
+    }
+  }
+
+  #[test]
+  fn test_ttol() {
+    let mut distmesh = circle().build();
+    let mut lazy = circle().ttol(0.1).build();
+    for _ in 0..200 {
+      distmesh.update(DELTA_T);
+      lazy.update(DELTA_T);
+    }
+    assert!(lazy.number_of_retriangulations() < distmesh.number_of_retriangulations());
+    assert!(avg_quality(&lazy.points, &lazy.triangulation.triangles) > 0.9);
+
+    // a tiny movement before the first update does not require a new triangulation
+    let mut lazy = circle().ttol(0.1).build();
+    lazy.points[0].x += 1e-6;
+    lazy.update(DELTA_T);
+    assert_eq!(lazy.number_of_retriangulations(), 0);
+  }
+
+  #[test]
+  fn test_run() {
+    let report = circle().build().run(&StopCriteria::new(20));
+    assert_eq!(report.reason, StopReason::MaxIterations);
+    assert_eq!(report.iterations, 20);
+
+    let report = circle().build().run(&StopCriteria::new(1000).max_duration(Duration::ZERO));
+    assert_eq!(report.reason, StopReason::Timeout);
+    assert_eq!(report.iterations, 1);
+
+    let report = circle().build().run(&StopCriteria::new(1000).avg_quality(0.9));
+    assert_eq!(report.reason, StopReason::AvgQuality);
+    assert!(report.avg_quality >= 0.9);
+    assert!(report.iterations < 1000);
+
+    let mut distmesh = circle().build();
+    let report = distmesh.run(&StopCriteria::new(2000).dptol(0.001));
+    assert_eq!(report.reason, StopReason::Converged);
+    assert!(distmesh.max_displacement() < 0.001);
+    assert!(report.min_quality > 0.5);
+  }
+
+  struct Counter {
+    calls: Rc<RefCell<Vec<&'static str>>>,
+    abort_at: usize,
+  }
+
+  impl Observer for Counter {
+    fn after_retriangulation(&mut self, view: &MeshView) -> ControlFlow<()> {
+      assert!(view.forces.is_none());
+      self.calls.borrow_mut().push("retriangulation");
+      ControlFlow::Continue(())
+    }
+
+    fn before_forces(&mut self, _view: &MeshView) -> ControlFlow<()> {
+      self.calls.borrow_mut().push("before");
+      ControlFlow::Continue(())
+    }
+
+    fn after_forces(&mut self, view: &MeshView) -> ControlFlow<()> {
+      assert_eq!(view.forces.unwrap().len(), view.points.len());
+      self.calls.borrow_mut().push("after");
+      if view.iteration == self.abort_at { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    }
+
+    fn after_pushback(&mut self, view: &MeshView) -> ControlFlow<()> {
+      assert!(view.domain_triangles().len() <= view.triangulation.triangles.len());
+      self.calls.borrow_mut().push("pushback");
+      ControlFlow::Continue(())
+    }
+  }
+
+  #[test]
+  fn test_observer() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let mut distmesh = circle().observer(Box::new(Counter {calls: calls.clone(), abort_at: 2})).build();
+    distmesh.update(DELTA_T);
+    distmesh.update(DELTA_T);
+    assert!(!distmesh.is_aborted());
+    assert_eq!(*calls.borrow(), ["before", "after", "pushback", "retriangulation", "before", "after", "pushback"]);
+
+    calls.borrow_mut().clear();
+    let report = distmesh.run(&StopCriteria::new(100));
+    assert_eq!(report.reason, StopReason::Aborted);
+    assert_eq!(report.iterations, 1);
+    assert!(distmesh.is_aborted());
+    assert_eq!(*calls.borrow(), ["retriangulation", "before", "after"]);
+  }
+
+  /// the setup of the circle example
+  fn circle_example() -> DistMeshBuilder {
+    DistMeshBuilder::new(600)
+      .x1(-350.0).x2(350.0)
+      .y1(-350.0).y2(350.0)
+      .virtual_edges()
+      .break_edges()
+      .bosson()
+      .dist_fn(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 300.0)))
+  }
+
+  /// the setup of the rect example (with fixpoints at its corners)
+  fn rect_example() -> DistMeshBuilder {
+    let mut builder = DistMeshBuilder::new(300)
+      .x1(-350.0).x2(350.0)
+      .y1(-350.0).y2(350.0)
+      .dist_fn(Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 300.0, 300.0)));
+    for (x, y) in [(-150.0, -150.0), (150.0, -150.0), (150.0, 150.0), (-150.0, 150.0)] {
+      builder = builder.add_fixpoint(Point {x, y});
+    }
+    builder
+  }
+
+  fn fingerprint(distmesh: &DistMesh) -> (usize, usize, f64, f64) {
+    let sum_x: f64 = distmesh.points.iter().map(|p| p.x).sum();
+    let sum_y: f64 = distmesh.points.iter().map(|p| p.y).sum();
+    (distmesh.points.len(), distmesh.triangulation.triangles.len() / 3, sum_x, sum_y)
+  }
+
+  #[test]
+  fn test_seed() {
+    let a = circle().seed(7).build();
+    let b = circle().seed(7).build();
+    let c = circle().seed(8).build();
+    assert_eq!(a.points, b.points);
+    assert_ne!(a.points, c.points);
+
+    let mut a = circle().seed(7).half_edge_mesh().build();
+    let mut b = circle().seed(7).half_edge_mesh().build();
+    a.run(&StopCriteria::new(50));
+    b.run(&StopCriteria::new(50));
+    assert_eq!(a.points, b.points);
+    assert_eq!(a.triangulation.triangles, b.triangulation.triangles);
+
+    let bbox = BoundigBox {x: -1.0, y: -1.0, w: 2.0, h: 2.0};
+    let circle_fn = || Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0));
+    let a = DistMesh::with_seed(100, bbox.clone(), circle_fn(), 7);
+    let b = DistMesh::with_seed(100, bbox, circle_fn(), 7);
+    assert_eq!(a.points.len(), 100);
+    assert_eq!(a.points, b.points);
+  }
+
+  /// average length of the edges of the domain triangles left of x = -0.5 and right of x = 0.5
+  fn avg_edge_lengths(distmesh: &DistMesh) -> (f64, f64) {
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    let triangles = &distmesh.triangulation.triangles;
+    for e in 0..triangles.len() {
+      let u = &distmesh.points[triangles[e]];
+      let v = &distmesh.points[triangles[next_halfedge(e)]];
+      let center = u.center(v);
+      if center.x < -0.5 { left.push(u.distance(v)); }
+      if center.x > 0.5 { right.push(u.distance(v)); }
+    }
+    (left.iter().sum::<f64>() / left.len() as f64, right.iter().sum::<f64>() / right.len() as f64)
+  }
+
+  #[test]
+  fn test_size_function() {
+    // the grading is only known at runtime and captured by the closure
+    let grading: f64 = "2.0".parse().unwrap();
+    let mut distmesh = circle().edge_len_fn(move |p: &Point| 1.0 + grading * (p.x + 1.0)).seed(3).build();
+    distmesh.run(&StopCriteria::new(100));
+    let (left, right) = avg_edge_lengths(&distmesh);
+    assert!(2.0 * left < right, "{} {}", left, right);
+
+    let size = DistanceGrading::new(Box::new(Circle::new(Point {x: -1.0, y: 0.0}, 0.0)), |d| 1.0 + 2.0 * d);
+    let mut distmesh = circle().edge_len_fn(size).seed(3).build();
+    distmesh.run(&StopCriteria::new(100));
+    let (left, right) = avg_edge_lengths(&distmesh);
+    assert!(2.0 * left < right, "{} {}", left, right);
+  }
+
+  #[test]
+  fn test_boundary_grading() {
+    let size = BoundaryGrading::new(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0)), 1.0, 4.0, 6.0);
+    let mut distmesh = circle().edge_len_fn(size).seed(3).build();
+    distmesh.run(&StopCriteria::new(100));
+
+    let (mut inner, mut outer) = (Vec::new(), Vec::new());
+    let triangles = &distmesh.triangulation.triangles;
+    for e in 0..triangles.len() {
+      let u = &distmesh.points[triangles[e]];
+      let v = &distmesh.points[triangles[next_halfedge(e)]];
+      let r = u.center(v).len();
+      if r < 0.4 { inner.push(u.distance(v)); }
+      if r > 0.85 { outer.push(u.distance(v)); }
+    }
+    let inner = inner.iter().sum::<f64>() / inner.len() as f64;
+    let outer = outer.iter().sum::<f64>() / outer.len() as f64;
+    assert!(2.0 * outer < inner, "{} {}", outer, inner);
+  }
+
+  #[test]
+  fn test_initial_mesh() {
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| Point {x, y});
+    let mut mesh = meshing::triangulator::triangulate(&corners.clone().map(|p| meshing::geometry::Point {x: p.x, y: p.y}));
+    meshing::refinement::RefinementBuilder::new().min_angle(25.0).max_area(0.01).build().refine(&mut mesh);
+    let n = mesh.iter_vertices().count();
+
+    let mut builder = DistMeshBuilder::new(0)
+      .x1(-1.0).x2(1.0)
+      .y1(-1.0).y2(1.0)
+      .dist_fn(Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 2.0)))
+      .edge_len_fn(0.1)
+      .initial_mesh(&mesh);
+    for corner in corners {
+      builder = builder.add_fixpoint(corner);
+    }
+    let mut distmesh = builder.build();
+    assert_eq!(distmesh.points.len(), n);
+    assert!(avg_quality(&distmesh.points, &distmesh.triangulation.triangles) > 0.8);
+
+    distmesh.run(&StopCriteria::new(20));
+    assert_eq!(distmesh.points.len(), n);
+    assert!(distmesh.points.iter().all(|p| distmesh.dist_fn.distance(p) < 1e-3));
+  }
+
+  #[test]
+  fn test_regression() {
+    let expected = [
+      (648, 1218, 523.9782891656765, 4028.450944217033),
+      (304, 545, -632.3484275368558, 1315.9417243576638),
+    ];
+    for (builder, expected) in [circle_example(), rect_example()].into_iter().zip(expected) {
+      let mut distmesh = builder.seed(42).build();
+      distmesh.run(&StopCriteria::new(100));
+      let (npoints, ntriangles, sum_x, sum_y) = fingerprint(&distmesh);
+      assert_eq!((npoints, ntriangles), (expected.0, expected.1));
+      assert!((sum_x - expected.2).abs() < 1e-6);
+      assert!((sum_y - expected.3).abs() < 1e-6);
+    }
+  }
+}
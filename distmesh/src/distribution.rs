@@ -0,0 +1,342 @@
+use delaunator::Point;
+use rand::Rng;
+use crate::distmesh::BoundigBox;
+use crate::geometry::{equiliteral_triangle, DSPoint};
+use crate::sfd::SignedDistanceFunction;
+use crate::size::SizeFunction;
+
+/// Number of cells in each direction of the grid used to estimate the density of the points.
+const DENSITY_GRID_SIZE: usize = 64;
+
+/// Number of candidates tested around an active point by the Poisson-disk sampler (as suggested by Bridson).
+const POISSON_DISK_CANDIDATES: usize = 30;
+
+/// Ratio between the integral of 1/h^2 and the number of points a maximal Poisson-disk sampling
+/// with radius h yields (estimated experimentally).
+const POISSON_DISK_PACKING: f64 = 0.64;
+
+/// Initial placement of the points of a [`crate::DistMesh`].
+/// Except for [`Initializer::Lattice`] the density of the points follows the edge length function h, i.e. it is proportional to 1/h(p)^2.
+/// The initial placement has a large impact on the number of iterations required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Initializer {
+  /// uniformly distributed random points, a point p is kept with a probability proportional to 1/h(p)^2
+  #[default]
+  Random,
+  /// the vertices of a hexagonal lattice of equilateral triangles, a vertex p is kept with a probability proportional to 1/h(p)^2 (as in the original DistMesh)
+  HexLattice,
+  /// all vertices of a hexagonal lattice of equilateral triangles inside the domain, i.e. the edge length function is ignored
+  Lattice,
+  /// random points of which no two points p, q are closer than c(h(p) + h(q))/2 where c is chosen such that there are approximately ```n``` points
+  PoissonDisk,
+}
+
+/// Returns (approximately, except for the random initializer) ```n``` points inside the domain of ```dist_fn``` whose density follows ```edge_len_fn```.
+pub(crate) fn distribute_points<R: Rng>(
+  n: usize,
+  initializer: Initializer,
+  bouding_box: &BoundigBox,
+  dist_fn: &dyn SignedDistanceFunction,
+  edge_len_fn: &dyn SizeFunction,
+  rng: &mut R) -> Vec<Point> {
+  let density = Density::estimate(bouding_box, dist_fn, edge_len_fn);
+  match initializer {
+    Initializer::Random => distribute_randomly(n, density, bouding_box, dist_fn, edge_len_fn, rng),
+    Initializer::HexLattice => distribute_on_lattice(n, density, bouding_box, dist_fn, edge_len_fn, rng),
+    Initializer::Lattice => distribute_on_regular_lattice(n, density, bouding_box, dist_fn),
+    Initializer::PoissonDisk => distribute_poisson_disk(n, density, bouding_box, dist_fn, edge_len_fn, rng),
+  }
+}
+
+/// The maximum of the density 1/h^2, its integral over the domain and the area of the domain, all estimated on a regular grid.
+struct Density {
+  max: f64,
+  integral: f64,
+  area: f64,
+}
+
+impl Density {
+  fn estimate(bouding_box: &BoundigBox, dist_fn: &dyn SignedDistanceFunction, edge_len_fn: &dyn SizeFunction) -> Self {
+    let dx = bouding_box.w / DENSITY_GRID_SIZE as f64;
+    let dy = bouding_box.h / DENSITY_GRID_SIZE as f64;
+    let mut max: f64 = 0.0;
+    let mut sum = 0.0;
+    let mut inside = 0;
+    for i in 0..DENSITY_GRID_SIZE {
+      for j in 0..DENSITY_GRID_SIZE {
+        let p = Point {x: bouding_box.x + (i as f64 + 0.5) * dx, y: bouding_box.y + (j as f64 + 0.5) * dy};
+        if dist_fn.distance(&p) < 0.0 {
+          let density = density(&p, edge_len_fn);
+          max = max.max(density);
+          sum += density;
+          inside += 1;
+        }
+      }
+    }
+    Density {max, integral: sum * dx * dy, area: inside as f64 * dx * dy}
+  }
+
+  /// Returns **true** if the point ```p``` with density ```density``` is kept, i.e. with probability ```density / self.max```.
+  /// The maximum is raised if the grid missed it.
+  fn accept<R: Rng>(&mut self, density: f64, rng: &mut R) -> bool {
+    self.max = self.max.max(density);
+    let ratio = density / self.max;
+    ratio >= 1.0 || rng.gen::<f64>() < ratio
+  }
+}
+
+fn density(p: &Point, edge_len_fn: &dyn SizeFunction) -> f64 {
+  let h = edge_len_fn.size(p);
+  1.0 / (h * h)
+}
+
+fn distribute_randomly<R: Rng>(
+  n: usize,
+  mut density: Density,
+  bouding_box: &BoundigBox,
+  dist_fn: &dyn SignedDistanceFunction,
+  edge_len_fn: &dyn SizeFunction,
+  rng: &mut R) -> Vec<Point> {
+  let mut points: Vec<Point>= Vec::with_capacity(n);
+  let mut count = 0;
+
+  while count < n {
+    let candidate = Point { 
+      x: bouding_box.x + bouding_box.w/2.0 + random_range(rng, -bouding_box.w/2.0, bouding_box.w/2.0),
+      y: bouding_box.y + bouding_box.h/2.0 + random_range(rng, -bouding_box.h/2.0, bouding_box.h/2.0)
+    };
+
+    if dist_fn.distance(&candidate) < 0.0 && density.accept(self::density(&candidate, edge_len_fn), rng) {
+      points.push(candidate);
+      count += 1;
+    }
+  }
+
+  points
+}
+
+fn distribute_on_lattice<R: Rng>(
+  n: usize,
+  mut density: Density,
+  bouding_box: &BoundigBox,
+  dist_fn: &dyn SignedDistanceFunction,
+  edge_len_fn: &dyn SizeFunction,
+  rng: &mut R) -> Vec<Point> {
+  if n == 0 || density.integral <= 0.0 {
+    return Vec::new();
+  }
+
+  // a lattice with spacing s has 2/(sqrt(3) s^2) points per area of which the fraction density/max is kept
+  let spacing = (2.0 * density.integral / (density.max * f64::sqrt(3.0) * n as f64)).sqrt();
+  lattice(bouding_box, spacing)
+    .filter(|candidate| dist_fn.distance(candidate) < 0.0 && density.accept(self::density(candidate, edge_len_fn), rng))
+    .collect()
+}
+
+fn distribute_on_regular_lattice(
+  n: usize,
+  density: Density,
+  bouding_box: &BoundigBox,
+  dist_fn: &dyn SignedDistanceFunction) -> Vec<Point> {
+  if n == 0 || density.area <= 0.0 {
+    return Vec::new();
+  }
+
+  let spacing = (2.0 * density.area / (f64::sqrt(3.0) * n as f64)).sqrt();
+  lattice(bouding_box, spacing)
+    .filter(|candidate| dist_fn.distance(candidate) < 0.0)
+    .collect()
+}
+
+/// Returns the vertices of the hexagonal lattice of equilateral triangles with edge length ```spacing``` covering the bounding box.
+fn lattice(bouding_box: &BoundigBox, spacing: f64) -> impl Iterator<Item = Point> + '_ {
+  let (_, u2, u3) = equiliteral_triangle(spacing);
+  let nrows = (bouding_box.h / u3.y).floor() as usize + 1;
+  let ncols = (bouding_box.w / u2.x).floor() as usize + 1;
+  (0..nrows).flat_map(move |row| {
+    let offset = if row % 2 == 1 { u3.x } else { 0.0 };
+    (0..ncols).map(move |col| Point {x: bouding_box.x + offset + col as f64 * u2.x, y: bouding_box.y + row as f64 * u3.y})
+  })
+}
+
+/// Variable-radius Poisson-disk sampling following Bridson's algorithm.
+/// If no active point is left, random darts are thrown to reach parts of the domain which are not connected.
+fn distribute_poisson_disk<R: Rng>(
+  n: usize,
+  density: Density,
+  bouding_box: &BoundigBox,
+  dist_fn: &dyn SignedDistanceFunction,
+  edge_len_fn: &dyn SizeFunction,
+  rng: &mut R) -> Vec<Point> {
+  if n == 0 || density.integral <= 0.0 {
+    return Vec::new();
+  }
+
+  let scale = (POISSON_DISK_PACKING * density.integral / n as f64).sqrt();
+  let radius = |p: &Point| scale * edge_len_fn.size(p);
+  let mut grid = BackgroundGrid::new(bouding_box, scale / density.max.sqrt());
+
+  let mut points: Vec<Point> = Vec::new();
+  let mut radii: Vec<f64> = Vec::new();
+  let mut active: Vec<usize> = Vec::new();
+  let mut max_radius: f64 = 0.0;
+
+  let is_free = |p: &Point, r: f64, grid: &BackgroundGrid, points: &[Point], radii: &[f64], max_radius: f64| {
+    grid.neighbours(p, (r + max_radius) / 2.0).all(|i| p.distance(&points[i]) >= (r + radii[i]) / 2.0)
+  };
+
+  let mut misses = 0;
+  while misses < POISSON_DISK_CANDIDATES {
+    let next = (!active.is_empty()).then(|| active.swap_remove(rng.gen_range(0..active.len())));
+    let candidate = match next {
+      Some(i) => {
+        let found = (0..POISSON_DISK_CANDIDATES).find_map(|_| {
+          let angle = random_range(rng, 0.0, 2.0 * std::f64::consts::PI);
+          let distance = random_range(rng, radii[i], 2.0 * radii[i]);
+          let candidate = Point {x: points[i].x + distance * angle.cos(), y: points[i].y + distance * angle.sin()};
+          (dist_fn.distance(&candidate) < 0.0 && is_free(&candidate, radius(&candidate), &grid, &points, &radii, max_radius))
+            .then_some(candidate)
+        });
+        if found.is_some() {
+          active.push(i);
+        }
+        found
+      },
+      None => {
+        let candidate = Point {
+          x: bouding_box.x + random_range(rng, 0.0, bouding_box.w),
+          y: bouding_box.y + random_range(rng, 0.0, bouding_box.h)
+        };
+        if dist_fn.distance(&candidate) < 0.0 && is_free(&candidate, radius(&candidate), &grid, &points, &radii, max_radius) {
+          misses = 0;
+          Some(candidate)
+        } else {
+          misses += 1;
+          None
+        }
+      },
+    };
+
+    if let Some(candidate) = candidate {
+      let r = radius(&candidate);
+      max_radius = max_radius.max(r);
+      grid.insert(&candidate, points.len());
+      active.push(points.len());
+      points.push(candidate);
+      radii.push(r);
+    }
+  }
+
+  points
+}
+
+/// Regular grid of buckets to find the points close to a position.
+struct BackgroundGrid {
+  x: f64,
+  y: f64,
+  cell_size: f64,
+  ncols: usize,
+  nrows: usize,
+  cells: Vec<Vec<usize>>,
+}
+
+impl BackgroundGrid {
+  fn new(bouding_box: &BoundigBox, cell_size: f64) -> Self {
+    let ncols = (bouding_box.w / cell_size).ceil().max(1.0) as usize;
+    let nrows = (bouding_box.h / cell_size).ceil().max(1.0) as usize;
+    BackgroundGrid {x: bouding_box.x, y: bouding_box.y, cell_size, ncols, nrows, cells: vec![Vec::new(); ncols * nrows]}
+  }
+
+  fn cell(&self, p: &Point) -> (usize, usize) {
+    let col = ((p.x - self.x) / self.cell_size).floor().clamp(0.0, (self.ncols - 1) as f64) as usize;
+    let row = ((p.y - self.y) / self.cell_size).floor().clamp(0.0, (self.nrows - 1) as f64) as usize;
+    (col, row)
+  }
+
+  fn insert(&mut self, p: &Point, index: usize) {
+    let (col, row) = self.cell(p);
+    self.cells[row * self.ncols + col].push(index);
+  }
+
+  /// Returns the indices of all points of the cells intersecting the square of side length 2 ```distance``` around ```p```.
+  fn neighbours(&self, p: &Point, distance: f64) -> impl Iterator<Item = usize> + '_ {
+    let (col1, row1) = self.cell(&Point {x: p.x - distance, y: p.y - distance});
+    let (col2, row2) = self.cell(&Point {x: p.x + distance, y: p.y + distance});
+    (row1..=row2).flat_map(move |row| (col1..=col2).flat_map(move |col| self.cells[row * self.ncols + col].iter().copied()))
+  }
+}
+
+fn random_range<R: Rng>(rng: &mut R, a: f64, b: f64) -> f64 {
+  let d = b-a;
+  a + rng.gen::<f64>()*d
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::distmesh::create_rng;
+  use crate::sfd::Rect;
+
+  fn square() -> (BoundigBox, Rect) {
+    (BoundigBox {x: -1.0, y: -1.0, w: 2.0, h: 2.0}, Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 2.0))
+  }
+
+  /// edges in the left half are half as long as in the right half, i.e. the density is four times larger
+  fn graded(p: &Point) -> f64 {
+    if p.x < 0.0 { 0.1 } else { 0.2 }
+  }
+
+  #[test]
+  fn test_random_follows_size_field() {
+    let (bbox, rect) = square();
+    let points = distribute_points(2000, Initializer::Random, &bbox, &rect, &graded, &mut create_rng(Some(42)));
+    assert_eq!(points.len(), 2000);
+    assert!(points.iter().all(|p| rect.distance(p) < 0.0));
+    let left = points.iter().filter(|p| p.x < 0.0).count() as f64;
+    let ratio = left / (points.len() as f64 - left);
+    assert!((3.5..4.5).contains(&ratio), "ratio {}", ratio);
+  }
+
+  #[test]
+  fn test_hex_lattice() {
+    let (bbox, rect) = square();
+    let points = distribute_points(1000, Initializer::HexLattice, &bbox, &rect, &1.0, &mut create_rng(Some(42)));
+    let n = points.len() as f64;
+    assert!((900.0..1100.0).contains(&n), "{} points", n);
+    assert!(points.iter().all(|p| rect.distance(p) < 0.0));
+
+    let points = distribute_points(1000, Initializer::HexLattice, &bbox, &rect, &graded, &mut create_rng(Some(42)));
+    let left = points.iter().filter(|p| p.x < 0.0).count() as f64;
+    let ratio = left / (points.len() as f64 - left);
+    assert!((3.5..4.5).contains(&ratio), "ratio {}", ratio);
+  }
+
+  #[test]
+  fn test_regular_lattice() {
+    let (bbox, rect) = square();
+    let points = distribute_points(1000, Initializer::Lattice, &bbox, &rect, &graded, &mut create_rng(Some(42)));
+    let n = points.len() as f64;
+    assert!((950.0..1050.0).contains(&n), "{} points", n);
+    let left = points.iter().filter(|p| p.x < 0.0).count() as f64;
+    assert!((left / n - 0.5).abs() < 0.05);
+  }
+
+  #[test]
+  fn test_poisson_disk() {
+    let (bbox, rect) = square();
+    let points = distribute_points(1000, Initializer::PoissonDisk, &bbox, &rect, &graded, &mut create_rng(Some(42)));
+    let n = points.len() as f64;
+    assert!((900.0..1100.0).contains(&n), "{} points", n);
+    assert!(points.iter().all(|p| rect.distance(p) < 0.0));
+    let left = points.iter().filter(|p| p.x < 0.0).count() as f64;
+    let ratio = left / (n - left);
+    assert!((3.0..5.0).contains(&ratio), "ratio {}", ratio);
+
+    // no two points are closer than the smallest radius
+    let min_distance = points.iter().enumerate()
+      .flat_map(|(i, p)| points[i+1..].iter().map(move |q| p.distance(q)))
+      .fold(f64::INFINITY, f64::min);
+    let scale = (POISSON_DISK_PACKING * Density::estimate(&bbox, &rect, &graded).integral / 1000.0).sqrt();
+    assert!(min_distance >= scale * 0.1 * 0.999, "{} < {}", min_distance, scale * 0.1);
+  }
+}
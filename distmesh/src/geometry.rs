@@ -1,5 +1,6 @@
 use delaunator::Point;
 
+#[allow(clippy::len_without_is_empty)]
 pub trait DSPoint {
   fn subtract(&self, other: &Self) -> Self;
   fn add(&self, other: &Self) -> Self;
@@ -66,7 +67,7 @@ impl DSPoint for Point {
   }
 
   fn center(&self, other: &Self) -> Self {
-    let du = other.subtract(&self).mult(0.5);
+    let du = other.subtract(self).mult(0.5);
     self.add(&du)
   }
   
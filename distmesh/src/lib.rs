@@ -1,7 +1,10 @@
 pub mod distmesh;
+pub mod distribution;
 pub mod geometry;
+pub mod observer;
 pub mod prelude;
 pub mod sfd;
+pub mod size;
 pub mod tikz;
 pub mod quality;
 
@@ -0,0 +1,45 @@
+use std::ops::ControlFlow;
+use delaunator::{Point, Triangulation};
+use crate::{distmesh::filter_triangles, sfd::SignedDistanceFunction};
+
+/// Read-only view on the state of a [`crate::DistMesh`] during an update which is passed to each [`Observer`].
+pub struct MeshView<'a> {
+  /// the number of updates finished before the current one
+  pub iteration: usize,
+  pub points: &'a [Point],
+  /// the triangulation of all points including triangles outside of the domain
+  pub triangulation: &'a Triangulation,
+  /// the forces acting on the points, only available after they were computed
+  pub forces: Option<&'a [Point]>,
+  pub dist_fn: &'a dyn SignedDistanceFunction,
+}
+
+impl MeshView<'_> {
+  /// Returns the triangles of the triangulation whose centroid lies inside the domain.
+  pub fn domain_triangles(&self) -> Vec<usize> {
+    filter_triangles(self.points, &self.triangulation.triangles, self.dist_fn)
+  }
+}
+
+/// Hooks which are called by [`crate::DistMesh::update`] in the order 
+/// ```after_retriangulation``` (only if the points were triangulated), ```before_forces```, ```after_forces``` and ```after_pushback```.
+/// Returning [`ControlFlow::Break`] aborts the update, see [`crate::DistMesh::is_aborted`].
+pub trait Observer {
+  fn after_retriangulation(&mut self, _view: &MeshView) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+  }
+
+  fn before_forces(&mut self, _view: &MeshView) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+  }
+
+  fn after_forces(&mut self, _view: &MeshView) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+  }
+
+  /// Is called after the points moved and were pushed back into the domain. 
+  /// Aborting at this point only marks the update as aborted since it is finished anyway.
+  fn after_pushback(&mut self, _view: &MeshView) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+  }
+}
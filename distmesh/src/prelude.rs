@@ -1,5 +1,8 @@
-pub use crate::distmesh::{DistMesh, DistMeshBuilder, EdgeLenFn, DELTA_T};
+pub use crate::distmesh::{DistMesh, DistMeshBuilder, RunReport, StopCriteria, StopReason, DELTA_T};
+pub use crate::distribution::Initializer;
+pub use crate::observer::{MeshView, Observer};
 pub use crate::sfd::{Circle, Ring, Rect, SignedDistanceFunction};
-pub use crate::quality::avg_quality;
+pub use crate::size::{BoundaryGrading, DistanceGrading, LocalFeatureSizeBuilder, SizeFunction, SizeGrid};
+pub use crate::quality::{avg_quality, min_quality};
 
 pub use delaunator::Point;
\ No newline at end of file
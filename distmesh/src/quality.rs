@@ -1,4 +1,4 @@
-use delaunator::{Point, triangulate, Triangulation, EMPTY};
+use delaunator::Point;
 use crate::geometry::DSPoint;
 
 /// Returns the average quality of triangles which is a metric for the quality of a triangular mesh.
@@ -40,6 +40,34 @@ pub fn avg_quality(points: &[Point], triangles: &[usize]) -> f64 {
   avg_quality / ntriagnles as f64
 }
 
+/// Returns the quality of the worst triangle, i.e. the minimum of all [`quality`] values.
+///
+/// # Arguments
+/// 
+/// * `points` - The slice of points of the triangulation
+/// * `triangles` - Indices of triangles where three consecutive indices form a triangle
+/// 
+/// # Examples
+/// 
+/// ```
+/// use distmesh::quality::{avg_quality, min_quality};
+/// use delaunator::{Point, triangulate};
+/// 
+/// let points = vec![
+///        Point { x: 0., y: 0. },
+///        Point { x: 1., y: 0. },
+///        Point { x: 1., y: 1. },
+///        Point { x: 0., y: 3. },
+///    ]; 
+/// let result = triangulate(&points);
+/// assert!(min_quality(&points, &result.triangles) < avg_quality(&points, &result.triangles));
+/// ```
+pub fn min_quality(points: &[Point], triangles: &[usize]) -> f64 {
+  triangles.chunks_exact(3)
+    .map(|t| quality(&points[t[0]], &points[t[1]], &points[t[2]]))
+    .fold(f64::INFINITY, f64::min)
+}
+
 /// Returns the quality of a triangle. This measurement is a metric for the quality of a triangular mesh.
 ///
 /// # Arguments
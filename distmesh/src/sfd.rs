@@ -1,5 +1,6 @@
 use delaunator::Point;
 use crate::geometry::DSPoint;
+use std::ops::{BitAnd, BitOr, Not, Sub};
 
 const EPSILON: f64 = 0.0001;
 
@@ -19,6 +20,16 @@ pub trait SignedDistanceFunction {
     fn grad(&self, point: &Point) -> Point {
         self.grad_with_eps(point, EPSILON)
     }
+
+    /// Returns the second derivatives (d_xx, d_xy, d_yy) approximated by central differences.
+    fn hessian_with_eps(&self, point: &Point, epsilon: f64) -> (f64, f64, f64) {
+        let d = |dx: f64, dy: f64| self.distance(&Point {x: point.x + dx * epsilon, y: point.y + dy * epsilon});
+        let dist = d(0.0, 0.0);
+        let dxx = (d(1.0, 0.0) - 2.0 * dist + d(-1.0, 0.0)) / (epsilon * epsilon);
+        let dyy = (d(0.0, 1.0) - 2.0 * dist + d(0.0, -1.0)) / (epsilon * epsilon);
+        let dxy = (d(1.0, 1.0) - d(1.0, -1.0) - d(-1.0, 1.0) + d(-1.0, -1.0)) / (4.0 * epsilon * epsilon);
+        (dxx, dxy, dyy)
+    }
 }
 
 #[derive(Clone)]
@@ -32,21 +43,9 @@ impl Rect {
     pub fn new(center: Point, width: f64, height: f64) -> Self {
         Rect { center, width, height }
     }
-
-    fn x_dist(&self, point: &Point) -> f64 {
-        (self.center.x-point.x).abs() - self.width/2.0
-    }
-
-    fn y_dist(&self, point: &Point) -> f64 {
-        (self.center.y-point.y).abs() - self.height/2.0
-    }
 }
 
 impl SignedDistanceFunction for Rect {
-    /*fn distance(&self, point: &Point) -> f64 {
-        f64::max(self.x_dist(point), self.y_dist(point))
-    }*/
-
     fn distance(&self, point: &Point) -> f64 {
         let dx = (point.x - self.center.x).abs() - self.width/2.0;
         let dy = (point.y - self.center.y).abs() - self.height/2.0;
@@ -61,23 +60,21 @@ impl SignedDistanceFunction for Rect {
         }
     }
 
-    /*fn grad_with_eps(&self, point: &Point, _: f64) -> Point {
-        let x_dist = self.x_dist(point);
-        let y_dist = self.y_dist(point);
-        if x_dist > y_dist {
-            if self.center.x < point.x {
-                Point {x: 1.0, y: 0.0}
-            } else {
-                Point {x: -1.0, y: 0.0}
-            }
+    /// Returns the exact gradient, ```epsilon``` is ignored.
+    fn grad_with_eps(&self, point: &Point, _: f64) -> Point {
+        let sx = (point.x - self.center.x).signum();
+        let sy = (point.y - self.center.y).signum();
+        let dx = (point.x - self.center.x).abs() - self.width/2.0;
+        let dy = (point.y - self.center.y).abs() - self.height/2.0;
+
+        if dx > 0.0 || dy > 0.0 {
+            Point {x: sx * dx.max(0.0), y: sy * dy.max(0.0)}.norm()
+        } else if dx > dy {
+            Point {x: sx, y: 0.0}
         } else {
-            if self.center.y < point.y {
-                Point {x: 0.0, y: 1.0}
-            } else {
-                Point {x: 0.0, y: -1.0}
-            }
+            Point {x: 0.0, y: sy}
         }
-    }*/
+    }
 }
 
 #[derive(Clone)]
@@ -96,8 +93,14 @@ impl SignedDistanceFunction for Circle {
     fn distance(&self, point: &Point) -> f64 {
         self.center.distance(point) - self.radius
     }
+
+    /// Returns the exact gradient, ```epsilon``` is ignored. At the center, where it is undefined, the zero vector is returned.
+    fn grad_with_eps(&self, point: &Point, _: f64) -> Point {
+        radial_direction(&self.center, point)
+    }
 }
 
+#[derive(Clone)]
 pub struct Ring {
     center: Point,
     inner_radius: f64,
@@ -121,6 +124,19 @@ impl SignedDistanceFunction for Ring {
         let len = (dx*dx + dy*dy).sqrt();
         (len-r1).abs() - r2
     }
+
+    /// Returns the exact gradient, ```epsilon``` is ignored. At the center, where it is undefined, the zero vector is returned.
+    fn grad_with_eps(&self, point: &Point, _: f64) -> Point {
+        let r1 = (self.outer_radius + self.inner_radius) / 2.0;
+        let sign = (self.center.distance(point) - r1).signum();
+        radial_direction(&self.center, point).mult(sign)
+    }
+}
+
+/// Returns the unit vector pointing from ```center``` to ```point``` or the zero vector if both are equal.
+fn radial_direction(center: &Point, point: &Point) -> Point {
+    let dir = point.subtract(center);
+    if dir.len() > 0.0 { dir.norm() } else { dir }
 }
 
 pub struct SDFUnion {
@@ -144,3 +160,373 @@ impl SDFUnion {
         SDFUnion { sdfs }
     }
 }
+
+/// Intersection of domains, i.e. the maximum of the signed distances.
+pub struct SDFIntersection {
+    sdfs: Vec<Box<dyn SignedDistanceFunction>>,
+}
+
+impl SignedDistanceFunction for SDFIntersection {
+    fn distance(&self, point: &Point) -> f64 {
+        let mut dist = f64::MIN;
+
+        for sdf in self.sdfs.iter() {
+            dist = dist.max(sdf.distance(point));
+        }
+
+        dist
+    }
+}
+
+impl SDFIntersection {
+    pub fn new(sdfs: Vec<Box<dyn SignedDistanceFunction>>) -> Self {
+        SDFIntersection { sdfs }
+    }
+}
+
+/// Domain of ```sdf``` without the domain of ```subtrahend```, i.e. max(d_1, -d_2).
+/// It is also created by ```-```, e.g. a plate with a hole:
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// let rect: Box<dyn SignedDistanceFunction> = Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 1.0));
+/// let hole: Box<dyn SignedDistanceFunction> = Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 0.25));
+/// let plate = rect - hole;
+/// assert!(plate.distance(&Point {x: 0.0, y: 0.0}) > 0.0);
+/// assert!(plate.distance(&Point {x: 0.5, y: 0.0}) < 0.0);
+/// ```
+pub struct SDFDifference {
+    sdf: Box<dyn SignedDistanceFunction>,
+    subtrahend: Box<dyn SignedDistanceFunction>,
+}
+
+impl SignedDistanceFunction for SDFDifference {
+    fn distance(&self, point: &Point) -> f64 {
+        f64::max(self.sdf.distance(point), -self.subtrahend.distance(point))
+    }
+}
+
+impl SDFDifference {
+    pub fn new(sdf: Box<dyn SignedDistanceFunction>, subtrahend: Box<dyn SignedDistanceFunction>) -> Self {
+        SDFDifference { sdf, subtrahend }
+    }
+}
+
+/// Complement of a domain, i.e. the negated signed distance.
+pub struct SDFComplement {
+    sdf: Box<dyn SignedDistanceFunction>,
+}
+
+impl SignedDistanceFunction for SDFComplement {
+    fn distance(&self, point: &Point) -> f64 {
+        -self.sdf.distance(point)
+    }
+}
+
+impl SDFComplement {
+    pub fn new(sdf: Box<dyn SignedDistanceFunction>) -> Self {
+        SDFComplement { sdf }
+    }
+}
+
+/// Smooth minimum used to blend the boundaries of two domains within the blending radius k.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blend {
+    /// quadratic polynomial smooth minimum, it equals the minimum if the distances differ by more than k
+    Polynomial,
+    /// exponential smooth minimum -k ln(exp(-a/k) + exp(-b/k)), it is smooth everywhere but never equals the minimum
+    Exponential,
+}
+
+impl Blend {
+    /// Returns the smooth minimum of the distances ```a``` and ```b``` and the weight of ```a```, i.e. its partial derivative
+    /// with respect to ```a```.
+    fn weighted_min(&self, a: f64, b: f64, radius: f64) -> (f64, f64) {
+        match self {
+            Blend::Polynomial => {
+                let h = (0.5 + 0.5 * (b - a) / radius).clamp(0.0, 1.0);
+                (h * a + (1.0 - h) * b - radius * h * (1.0 - h), h)
+            },
+            Blend::Exponential => {
+                let min = a.min(b);
+                let (ea, eb) = (((min - a) / radius).exp(), ((min - b) / radius).exp());
+                (min - radius * (ea + eb).ln(), ea / (ea + eb))
+            },
+        }
+    }
+
+    fn min(&self, a: f64, b: f64, radius: f64) -> f64 {
+        self.weighted_min(a, b, radius).0
+    }
+
+    fn max(&self, a: f64, b: f64, radius: f64) -> f64 {
+        -self.min(-a, -b, radius)
+    }
+
+    /// Returns the smooth minimum of the distances ```a``` and ```b``` and its gradient computed by the chain rule
+    /// from the gradients ```grad_a``` and ```grad_b```.
+    fn smooth_min(&self, (a, grad_a): (f64, Point), (b, grad_b): (f64, Point), radius: f64) -> (f64, Point) {
+        let (dist, weight) = self.weighted_min(a, b, radius);
+        (dist, grad_a.mult(weight).add(&grad_b.mult(1.0 - weight)))
+    }
+
+    fn smooth_max(&self, (a, grad_a): (f64, Point), (b, grad_b): (f64, Point), radius: f64) -> (f64, Point) {
+        let (dist, grad) = self.smooth_min((-a, grad_a.mult(-1.0)), (-b, grad_b.mult(-1.0)), radius);
+        (-dist, grad.mult(-1.0))
+    }
+}
+
+/// Union of domains whose boundaries are blended within the distance ```radius```, e.g. to model fillets.
+/// The gradient is computed by the chain rule from the gradients of the domains, thus, it is exact for domains with exact gradients
+/// such as [`Rect`], [`Circle`] and [`Ring`].
+///
+/// ```
+/// use distmesh::prelude::*;
+/// use distmesh::sfd::{Blend, SDFSmoothUnion};
+///
+/// let union = SDFSmoothUnion::new(vec![
+///     Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 0.5)),
+///     Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 0.5, 2.0)),
+/// ], 0.2, Blend::Polynomial);
+/// // the reentrant corner at (0.25, 0.25) is filled
+/// assert!(union.distance(&Point {x: 0.26, y: 0.26}) < 0.0);
+/// // away from the corners nothing changes
+/// assert!((union.distance(&Point {x: 0.9, y: 0.0}) + 0.1).abs() < 1e-10);
+/// ```
+pub struct SDFSmoothUnion {
+    sdfs: Vec<Box<dyn SignedDistanceFunction>>,
+    radius: f64,
+    blend: Blend,
+}
+
+impl SDFSmoothUnion {
+    pub fn new(sdfs: Vec<Box<dyn SignedDistanceFunction>>, radius: f64, blend: Blend) -> Self {
+        assert!(!sdfs.is_empty());
+        assert!(radius > 0.0);
+        SDFSmoothUnion { sdfs, radius, blend }
+    }
+
+    fn distance_and_grad(&self, point: &Point, epsilon: f64) -> (f64, Point) {
+        self.sdfs.iter()
+            .map(|sdf| (sdf.distance(point), sdf.grad_with_eps(point, epsilon)))
+            .reduce(|a, b| self.blend.smooth_min(a, b, self.radius))
+            .expect("expect at least one signed distance function")
+    }
+}
+
+impl SignedDistanceFunction for SDFSmoothUnion {
+    fn distance(&self, point: &Point) -> f64 {
+        self.sdfs.iter()
+            .map(|sdf| sdf.distance(point))
+            .reduce(|a, b| self.blend.min(a, b, self.radius))
+            .expect("expect at least one signed distance function")
+    }
+
+    fn grad_with_eps(&self, point: &Point, epsilon: f64) -> Point {
+        self.distance_and_grad(point, epsilon).1
+    }
+}
+
+/// Intersection of domains whose boundaries are blended within the distance ```radius```, see [`SDFSmoothUnion`].
+pub struct SDFSmoothIntersection {
+    sdfs: Vec<Box<dyn SignedDistanceFunction>>,
+    radius: f64,
+    blend: Blend,
+}
+
+impl SDFSmoothIntersection {
+    pub fn new(sdfs: Vec<Box<dyn SignedDistanceFunction>>, radius: f64, blend: Blend) -> Self {
+        assert!(!sdfs.is_empty());
+        assert!(radius > 0.0);
+        SDFSmoothIntersection { sdfs, radius, blend }
+    }
+
+    fn distance_and_grad(&self, point: &Point, epsilon: f64) -> (f64, Point) {
+        self.sdfs.iter()
+            .map(|sdf| (sdf.distance(point), sdf.grad_with_eps(point, epsilon)))
+            .reduce(|a, b| self.blend.smooth_max(a, b, self.radius))
+            .expect("expect at least one signed distance function")
+    }
+}
+
+impl SignedDistanceFunction for SDFSmoothIntersection {
+    fn distance(&self, point: &Point) -> f64 {
+        self.sdfs.iter()
+            .map(|sdf| sdf.distance(point))
+            .reduce(|a, b| self.blend.max(a, b, self.radius))
+            .expect("expect at least one signed distance function")
+    }
+
+    fn grad_with_eps(&self, point: &Point, epsilon: f64) -> Point {
+        self.distance_and_grad(point, epsilon).1
+    }
+}
+
+/// Domain of ```sdf``` without the domain of ```subtrahend``` whose boundaries are blended within the distance ```radius```,
+/// see [`SDFSmoothUnion`].
+pub struct SDFSmoothDifference {
+    sdf: Box<dyn SignedDistanceFunction>,
+    subtrahend: Box<dyn SignedDistanceFunction>,
+    radius: f64,
+    blend: Blend,
+}
+
+impl SDFSmoothDifference {
+    pub fn new(sdf: Box<dyn SignedDistanceFunction>, subtrahend: Box<dyn SignedDistanceFunction>, radius: f64, blend: Blend) -> Self {
+        assert!(radius > 0.0);
+        SDFSmoothDifference { sdf, subtrahend, radius, blend }
+    }
+
+    fn distance_and_grad(&self, point: &Point, epsilon: f64) -> (f64, Point) {
+        let a = (self.sdf.distance(point), self.sdf.grad_with_eps(point, epsilon));
+        let b = (-self.subtrahend.distance(point), self.subtrahend.grad_with_eps(point, epsilon).mult(-1.0));
+        self.blend.smooth_max(a, b, self.radius)
+    }
+}
+
+impl SignedDistanceFunction for SDFSmoothDifference {
+    fn distance(&self, point: &Point) -> f64 {
+        self.blend.max(self.sdf.distance(point), -self.subtrahend.distance(point), self.radius)
+    }
+
+    fn grad_with_eps(&self, point: &Point, epsilon: f64) -> Point {
+        self.distance_and_grad(point, epsilon).1
+    }
+}
+
+/// Union of two domains, see [`SDFUnion`].
+///
+/// Together with ```&```, ```-``` and ```!``` domains can be composed in one expression.
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// let left: Box<dyn SignedDistanceFunction> = Box::new(Circle::new(Point {x: -0.5, y: 0.0}, 0.5));
+/// let right: Box<dyn SignedDistanceFunction> = Box::new(Circle::new(Point {x: 0.5, y: 0.0}, 0.5));
+/// let both = left | right;
+/// assert!(both.distance(&Point {x: -0.5, y: 0.0}) < 0.0);
+/// assert!(both.distance(&Point {x: 0.5, y: 0.0}) < 0.0);
+/// assert!(both.distance(&Point {x: 0.0, y: 0.5}) > 0.0);
+/// ```
+impl BitOr for Box<dyn SignedDistanceFunction> {
+    type Output = Box<dyn SignedDistanceFunction>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Box::new(SDFUnion::new(vec![self, rhs]))
+    }
+}
+
+/// Intersection of two domains, see [`SDFIntersection`].
+impl BitAnd for Box<dyn SignedDistanceFunction> {
+    type Output = Box<dyn SignedDistanceFunction>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Box::new(SDFIntersection::new(vec![self, rhs]))
+    }
+}
+
+/// Difference of two domains, see [`SDFDifference`].
+impl Sub for Box<dyn SignedDistanceFunction> {
+    type Output = Box<dyn SignedDistanceFunction>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Box::new(SDFDifference::new(self, rhs))
+    }
+}
+
+/// Complement of a domain, see [`SDFComplement`].
+impl Not for Box<dyn SignedDistanceFunction> {
+    type Output = Box<dyn SignedDistanceFunction>;
+
+    fn not(self) -> Self::Output {
+        Box::new(SDFComplement::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Box<dyn SignedDistanceFunction> {
+        Box::new(Rect::new(Point {x, y}, width, height))
+    }
+
+    #[test]
+    fn test_l_shape() {
+        // [-1, 1] x [-1, 1] without the upper right quarter
+        let l_shape = rect(0.0, 0.0, 2.0, 2.0) - rect(0.5, 0.5, 1.0, 1.0);
+        assert!((l_shape.distance(&Point {x: -0.5, y: -0.5}) + 0.5).abs() < 1e-10);
+        assert!((l_shape.distance(&Point {x: 0.5, y: 0.5}) - 0.5).abs() < 1e-10);
+        assert!((l_shape.distance(&Point {x: 0.5, y: -0.25}) + 0.25).abs() < 1e-10);
+
+        let same = rect(0.0, 0.0, 2.0, 2.0) & !rect(0.5, 0.5, 1.0, 1.0);
+        for p in [Point {x: -0.5, y: -0.5}, Point {x: 0.5, y: 0.5}, Point {x: 0.9, y: -0.3}] {
+            assert_eq!(l_shape.distance(&p), same.distance(&p));
+        }
+    }
+
+    fn circle(x: f64, y: f64, radius: f64) -> Box<dyn SignedDistanceFunction> {
+        Box::new(Circle::new(Point {x, y}, radius))
+    }
+
+    /// compares the gradient, as used by DistMesh, with central differences of the distance
+    fn assert_grad(sdf: &dyn SignedDistanceFunction, point: &Point) {
+        let eps = 1e-6;
+        let d = |dx: f64, dy: f64| sdf.distance(&Point {x: point.x + dx, y: point.y + dy});
+        let expected = Point {x: (d(eps, 0.0) - d(-eps, 0.0)) / (2.0 * eps), y: (d(0.0, eps) - d(0.0, -eps)) / (2.0 * eps)};
+        let grad = sdf.grad(point);
+        assert!(grad.distance(&expected) < 1e-6, "{:?} {:?}", grad, expected);
+    }
+
+    #[test]
+    fn test_primitive_grad() {
+        let rect = Rect::new(Point {x: 0.5, y: 0.0}, 2.0, 1.0);
+        let ring = Ring::new(Point {x: 0.5, y: 0.0}, 0.5, 1.0);
+        let points = [Point {x: 0.3, y: 0.2}, Point {x: 1.2, y: -0.1}, Point {x: 2.0, y: 0.9}, Point {x: -1.0, y: -0.1}, Point {x: 0.6, y: 0.1}];
+        for p in points.iter() {
+            assert_grad(&rect, p);
+            assert_grad(circle(0.5, 0.0, 1.0).as_ref(), p);
+            assert_grad(&ring, p);
+        }
+        assert_eq!(Circle::new(Point {x: 0.5, y: 0.0}, 1.0).grad(&Point {x: 0.5, y: 0.0}), Point {x: 0.0, y: 0.0});
+    }
+
+    #[test]
+    fn test_smooth_operators() {
+        let points = [Point {x: 0.0, y: 0.3}, Point {x: 0.5, y: 0.1}, Point {x: -0.9, y: 0.2}, Point {x: 0.1, y: -0.7}];
+        for blend in [Blend::Polynomial, Blend::Exponential] {
+            let union = SDFSmoothUnion::new(vec![circle(-0.5, 0.0, 0.6), circle(0.5, 0.0, 0.6)], 0.2, blend);
+            let intersection = SDFSmoothIntersection::new(vec![circle(-0.5, 0.0, 0.6), circle(0.5, 0.0, 0.6)], 0.2, blend);
+            let difference = SDFSmoothDifference::new(circle(-0.5, 0.0, 0.6), circle(0.5, 0.0, 0.6), 0.2, blend);
+            for p in points.iter() {
+                let (a, b) = (circle(-0.5, 0.0, 0.6).distance(p), circle(0.5, 0.0, 0.6).distance(p));
+                // the blended distances are bounded by the sharp ones
+                assert!(union.distance(p) <= a.min(b));
+                assert!(intersection.distance(p) >= a.max(b));
+                assert!(difference.distance(p) >= a.max(-b));
+                // the distance without gradients agrees with the one computed along with the gradient
+                assert_eq!(union.distance(p), union.distance_and_grad(p, EPSILON).0);
+                assert_eq!(intersection.distance(p), intersection.distance_and_grad(p, EPSILON).0);
+                assert_eq!(difference.distance(p), difference.distance_and_grad(p, EPSILON).0);
+                assert_grad(&union, p);
+                assert_grad(&intersection, p);
+                assert_grad(&difference, p);
+            }
+        }
+
+        // the polynomial blend is exact outside of the blending radius
+        let union = SDFSmoothUnion::new(vec![circle(-0.5, 0.0, 0.6), circle(0.5, 0.0, 0.6)], 0.2, Blend::Polynomial);
+        assert_eq!(union.distance(&Point {x: -0.9, y: 0.2}), circle(-0.5, 0.0, 0.6).distance(&Point {x: -0.9, y: 0.2}));
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let cross = rect(0.0, 0.0, 2.0, 0.5) | rect(0.0, 0.0, 0.5, 2.0);
+        let center = rect(0.0, 0.0, 2.0, 0.5) & rect(0.0, 0.0, 0.5, 2.0);
+        assert!(cross.distance(&Point {x: 0.9, y: 0.0}) < 0.0);
+        assert!(cross.distance(&Point {x: 0.9, y: 0.9}) > 0.0);
+        assert!(center.distance(&Point {x: 0.9, y: 0.0}) > 0.0);
+        assert!((center.distance(&Point {x: 0.0, y: 0.0}) + 0.25).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,487 @@
+use delaunator::Point;
+use crate::distmesh::BoundigBox;
+use crate::geometry::DSPoint;
+use crate::sfd::SignedDistanceFunction;
+
+/// The (relative) desired edge length h(p) at a point p of the domain, often called size function or size field.
+/// Only the ratios between different sizes matter since the edge lengths are scaled to the number of points.
+///
+/// It is implemented for constants and closures, e.g.
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// let h_min = 0.5;
+/// let graded = move |p: &Point| h_min + p.x.abs();
+/// assert_eq!(graded.size(&Point {x: 1.0, y: 0.0}), 1.5);
+/// assert_eq!(2.0.size(&Point {x: 1.0, y: 0.0}), 2.0);
+/// ```
+pub trait SizeFunction {
+  fn size(&self, point: &Point) -> f64;
+}
+
+impl SizeFunction for f64 {
+  fn size(&self, _: &Point) -> f64 {
+    *self
+  }
+}
+
+impl<F: Fn(&Point) -> f64> SizeFunction for F {
+  fn size(&self, point: &Point) -> f64 {
+    self(point)
+  }
+}
+
+impl SizeFunction for Box<dyn SizeFunction> {
+  fn size(&self, point: &Point) -> f64 {
+    self.as_ref().size(point)
+  }
+}
+
+/// Size function h(p) = grading(d(p)) derived from the signed distance d of a [`SignedDistanceFunction`].
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// // edges become longer the farther they are away from the boundary of the rectangle
+/// let size = DistanceGrading::new(Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 500.0, 500.0)), |d| 1.0 + d.abs() / 500.0);
+/// assert_eq!(size.size(&Point {x: 0.0, y: 0.0}), 1.5);
+/// ```
+pub struct DistanceGrading<F: Fn(f64) -> f64> {
+  dist_fn: Box<dyn SignedDistanceFunction>,
+  grading: F,
+}
+
+impl<F: Fn(f64) -> f64> DistanceGrading<F> {
+  pub fn new(dist_fn: Box<dyn SignedDistanceFunction>, grading: F) -> Self {
+    DistanceGrading { dist_fn, grading }
+  }
+}
+
+impl<F: Fn(f64) -> f64> SizeFunction for DistanceGrading<F> {
+  fn size(&self, point: &Point) -> f64 {
+    (self.grading)(self.dist_fn.distance(point))
+  }
+}
+
+/// Size function which is fine close to the boundary and coarse inside the domain:
+/// h(p) = min(h_max, h_min + g|d(p)|) where d is the signed distance to the boundary and g the gradation.
+/// A gradation g limits the ratio of the lengths of neighbouring edges to approximately 1 + g.
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// let size = BoundaryGrading::new(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0)), 0.1, 0.5, 0.5);
+/// assert_eq!(size.size(&Point {x: 1.0, y: 0.0}), 0.1);
+/// assert!((size.size(&Point {x: 0.5, y: 0.0}) - 0.35).abs() < 1e-10);
+/// assert_eq!(size.size(&Point {x: 0.0, y: 0.0}), 0.5);
+/// ```
+pub struct BoundaryGrading {
+  dist_fn: Box<dyn SignedDistanceFunction>,
+  h_min: f64,
+  h_max: f64,
+  gradation: f64,
+}
+
+impl BoundaryGrading {
+  pub fn new(dist_fn: Box<dyn SignedDistanceFunction>, h_min: f64, h_max: f64, gradation: f64) -> Self {
+    assert!(0.0 < h_min && h_min <= h_max);
+    assert!(gradation >= 0.0);
+    BoundaryGrading { dist_fn, h_min, h_max, gradation }
+  }
+}
+
+impl SizeFunction for BoundaryGrading {
+  fn size(&self, point: &Point) -> f64 {
+    f64::min(self.h_max, self.h_min + self.gradation * self.dist_fn.distance(point).abs())
+  }
+}
+
+/// Size function defined by its values on the nodes of a regular background grid and bilinear interpolation in between.
+/// Outside of the grid the value of the closest node is used.
+pub struct SizeGrid {
+  bounding_box: BoundigBox,
+  cell_size: f64,
+  nx: usize,
+  ny: usize,
+  values: Vec<f64>,
+}
+
+impl SizeGrid {
+  /// Creates a grid of square cells with side length ```cell_size``` covering the bounding box and evaluates ```f``` at its nodes.
+  fn from_fn<F: Fn(&Point) -> f64>(bounding_box: BoundigBox, cell_size: f64, f: F) -> Self {
+    let nx = (bounding_box.w / cell_size).ceil() as usize + 1;
+    let ny = (bounding_box.h / cell_size).ceil() as usize + 1;
+    let mut grid = SizeGrid { bounding_box, cell_size, nx, ny, values: Vec::with_capacity(nx * ny) };
+    for j in 0..ny {
+      for i in 0..nx {
+        let value = f(&grid.node(i, j));
+        grid.values.push(value);
+      }
+    }
+    grid
+  }
+
+  /// Samples ```size_fn``` on a background grid covering [x1, x2] x [y1, y2] with ```resolution``` cells along the longer side.
+  ///
+  /// ```
+  /// use distmesh::prelude::*;
+  ///
+  /// let abrupt = |p: &Point| if p.x < 0.0 { 0.1 } else { 1.0 };
+  /// let size = SizeGrid::sample(&abrupt, -1.0, -1.0, 1.0, 1.0, 100).limit_gradient(0.3);
+  /// assert!((size.size(&Point {x: 0.5, y: 0.0}) - 0.25).abs() < 0.02);
+  /// let distmesh = DistMeshBuilder::new(100)
+  ///     .x1(-1.0).x2(1.0).y1(-1.0).y2(1.0)
+  ///     .dist_fn(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0)))
+  ///     .edge_len_fn(size)
+  ///     .build();
+  /// ```
+  pub fn sample<S: SizeFunction + ?Sized>(size_fn: &S, x1: f64, y1: f64, x2: f64, y2: f64, resolution: usize) -> Self {
+    assert!(x1 < x2);
+    assert!(y1 < y2);
+    assert!(resolution > 0);
+    let bounding_box = BoundigBox {x: x1, y: y1, w: x2 - x1, h: y2 - y1};
+    let cell_size = f64::max(bounding_box.w, bounding_box.h) / resolution as f64;
+    SizeGrid::from_fn(bounding_box, cell_size, |p| size_fn.size(p))
+  }
+
+  /// Decreases the sizes such that |∇h| <= ```gradation```, i.e. h(p) = min_q h(q) + g|p - q|,
+  /// by solving the gradient limiting equation by fast sweeping with a Godunov upwind discretization.
+  pub fn limit_gradient(mut self, gradation: f64) -> Self {
+    assert!(gradation >= 0.0);
+    let step = gradation * self.cell_size;
+    let (nx, ny) = (self.nx as isize, self.ny as isize);
+    let value = |values: &[f64], i: isize, j: isize| {
+      if i < 0 || j < 0 || i >= nx || j >= ny { f64::INFINITY } else { values[(j * nx + i) as usize] }
+    };
+
+    let mut changed = true;
+    while changed {
+      changed = false;
+      for (di, dj) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+        for sj in 0..ny {
+          let j = if dj > 0 { sj } else { ny - 1 - sj };
+          for si in 0..nx {
+            let i = if di > 0 { si } else { nx - 1 - si };
+            let a = f64::min(value(&self.values, i - 1, j), value(&self.values, i + 1, j));
+            let b = f64::min(value(&self.values, i, j - 1), value(&self.values, i, j + 1));
+            let h = if (a - b).abs() >= step {
+              f64::min(a, b) + step
+            } else {
+              (a + b + (2.0 * step * step - (a - b) * (a - b)).sqrt()) / 2.0
+            };
+            let k = (j * nx + i) as usize;
+            // ignore changes in the order of rounding errors to guarantee termination
+            if h < self.values[k] * (1.0 - 1e-12) {
+              self.values[k] = h;
+              changed = true;
+            }
+          }
+        }
+      }
+    }
+    self
+  }
+
+  fn node(&self, i: usize, j: usize) -> Point {
+    Point {x: self.bounding_box.x + i as f64 * self.cell_size, y: self.bounding_box.y + j as f64 * self.cell_size}
+  }
+
+  fn value(&self, i: usize, j: usize) -> f64 {
+    self.values[j * self.nx + i]
+  }
+}
+
+impl SizeFunction for SizeGrid {
+  fn size(&self, point: &Point) -> f64 {
+    let s = ((point.x - self.bounding_box.x) / self.cell_size).clamp(0.0, (self.nx - 1) as f64);
+    let t = ((point.y - self.bounding_box.y) / self.cell_size).clamp(0.0, (self.ny - 1) as f64);
+    let i = (s.floor() as usize).min(self.nx.saturating_sub(2));
+    let j = (t.floor() as usize).min(self.ny.saturating_sub(2));
+    let (s, t) = (s - i as f64, t - j as f64);
+    let (i1, j1) = ((i + 1).min(self.nx - 1), (j + 1).min(self.ny - 1));
+
+    (1.0 - s) * (1.0 - t) * self.value(i, j) + s * (1.0 - t) * self.value(i1, j) +
+      (1.0 - s) * t * self.value(i, j1) + s * t * self.value(i1, j1)
+  }
+}
+
+/// Builds a size function adapted to the geometry of the domain (as described by Persson and Strang):
+/// h(p) = min(h_curv(p), h_lfs(p)) clamped to [h_min, h_max] where
+///
+/// + h_curv(p) = 1/(K |κ(b)|) + g|d(p)| resolves the curvature κ of the boundary at the boundary point b closest to p
+///   with K elements per radian, the curvature is computed from the Hessian of the signed distance function d,
+/// + h_lfs(p) = 2 lfs(p) / R places R elements across narrow gaps where the local feature size lfs(p) = |d(p)| + d_M(p)
+///   is the sum of the distances to the boundary and to the medial axis.
+///
+/// The medial axis is estimated on a background grid: it crosses the grid edges whose end nodes have strongly diverging gradients.
+/// The distance d_M to it is propagated over the grid by fast sweeping.
+/// The result is stored on the same grid, see [`SizeGrid`], and can be smoothed by [`SizeGrid::limit_gradient`].
+///
+/// ```
+/// use distmesh::prelude::*;
+///
+/// // a thin rectangle, i.e. a narrow gap of width 0.2 which is resolved by 4 elements
+/// let size = LocalFeatureSizeBuilder::new(Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 0.2)))
+///     .x1(-1.0).x2(1.0).y1(-0.1).y2(0.1)
+///     .elements_per_gap(4.0)
+///     .build();
+/// assert!((size.size(&Point {x: 0.0, y: 0.0}) - 0.05).abs() < 0.01);
+/// ```
+pub struct LocalFeatureSizeBuilder {
+  dist_fn: Box<dyn SignedDistanceFunction>,
+  x1: f64,
+  y1: f64,
+  x2: f64,
+  y2: f64,
+  resolution: usize,
+  elements_per_radian: f64,
+  elements_per_gap: f64,
+  gradation: f64,
+  h_min: Option<f64>,
+  h_max: Option<f64>,
+}
+
+impl LocalFeatureSizeBuilder {
+  pub fn new(dist_fn: Box<dyn SignedDistanceFunction>) -> Self {
+    LocalFeatureSizeBuilder {
+      dist_fn,
+      x1: 0.0, y1: 0.0, x2: 1.0, y2: 1.0,
+      resolution: 100,
+      elements_per_radian: 1.0,
+      elements_per_gap: 3.0,
+      gradation: 0.3,
+      h_min: None,
+      h_max: None,
+    }
+  }
+
+  pub fn x1(mut self, x1: f64) -> Self {
+    self.x1 = x1;
+    self
+  }
+
+  pub fn x2(mut self, x2: f64) -> Self {
+    self.x2 = x2;
+    self
+  }
+
+  pub fn y1(mut self, y1: f64) -> Self {
+    self.y1 = y1;
+    self
+  }
+
+  pub fn y2(mut self, y2: f64) -> Self {
+    self.y2 = y2;
+    self
+  }
+
+  /// Number of cells of the background grid along the longer side of the bounding box, the default is 100.
+  pub fn resolution(mut self, resolution: usize) -> Self {
+    assert!(resolution > 0);
+    self.resolution = resolution;
+    self
+  }
+
+  /// Number of elements K per radian of the boundary, the default is 1.
+  pub fn elements_per_radian(mut self, elements_per_radian: f64) -> Self {
+    assert!(elements_per_radian > 0.0);
+    self.elements_per_radian = elements_per_radian;
+    self
+  }
+
+  /// Number of elements R across narrow gaps, the default is 3.
+  pub fn elements_per_gap(mut self, elements_per_gap: f64) -> Self {
+    assert!(elements_per_gap > 0.0);
+    self.elements_per_gap = elements_per_gap;
+    self
+  }
+
+  /// Growth g of the curvature based size away from the boundary, the default is 0.3.
+  pub fn gradation(mut self, gradation: f64) -> Self {
+    assert!(gradation >= 0.0);
+    self.gradation = gradation;
+    self
+  }
+
+  /// Lower bound of the size, the default is the cell size of the background grid.
+  pub fn h_min(mut self, h_min: f64) -> Self {
+    self.h_min = Some(h_min);
+    self
+  }
+
+  /// Upper bound of the size, the default is the longer side of the bounding box.
+  pub fn h_max(mut self, h_max: f64) -> Self {
+    self.h_max = Some(h_max);
+    self
+  }
+
+  pub fn build(self) -> SizeGrid {
+    assert!(self.x1 < self.x2);
+    assert!(self.y1 < self.y2);
+
+    let bounding_box = BoundigBox {x: self.x1, y: self.y1, w: self.x2 - self.x1, h: self.y2 - self.y1};
+    let cell_size = f64::max(bounding_box.w, bounding_box.h) / self.resolution as f64;
+    let h_min = self.h_min.unwrap_or(cell_size);
+    let h_max = self.h_max.unwrap_or(f64::max(bounding_box.w, bounding_box.h));
+    assert!(0.0 < h_min && h_min <= h_max);
+
+    let medial_distance = self.medial_axis_distance(SizeGrid::from_fn(bounding_box.clone(), cell_size, |p| self.dist_fn.distance(p)));
+    let dist_fn = self.dist_fn.as_ref();
+    SizeGrid::from_fn(bounding_box, cell_size, |p| {
+      let d = dist_fn.distance(p);
+
+      // curvature of the boundary at the closest boundary point
+      let b = p.subtract(&dist_fn.grad(p).mult(d));
+      let h_curv = 1.0 / (self.elements_per_radian * curvature(dist_fn, &b, cell_size).abs()) + self.gradation * d.abs();
+
+      let h_lfs = 2.0 * (d.abs() + medial_distance.size(p)) / self.elements_per_gap;
+
+      f64::min(h_curv, h_lfs).clamp(h_min, h_max)
+    })
+  }
+
+  /// Returns the distances to the medial axis at the nodes of the grid of ```distances```. The nodes around each point of
+  /// the medial axis are initialized exactly, all other nodes by solving |∇d| = 1 by fast sweeping.
+  fn medial_axis_distance(&self, distances: SizeGrid) -> SizeGrid {
+    let medial_axis = self.medial_axis(&distances);
+    let mut grid = SizeGrid { values: vec![f64::INFINITY; distances.values.len()], ..distances };
+    for m in medial_axis.iter() {
+      let i = (((m.x - grid.bounding_box.x) / grid.cell_size).floor().max(0.0) as usize).min(grid.nx - 1);
+      let j = (((m.y - grid.bounding_box.y) / grid.cell_size).floor().max(0.0) as usize).min(grid.ny - 1);
+      for (k, l) in [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)] {
+        if k < grid.nx && l < grid.ny {
+          let d = m.distance(&grid.node(k, l));
+          let value = &mut grid.values[l * grid.nx + k];
+          *value = value.min(d);
+        }
+      }
+    }
+    grid.limit_gradient(1.0)
+  }
+
+  /// Returns points of the medial axis, i.e. points on the grid edges inside the domain whose end nodes have
+  /// gradients enclosing an angle larger than 45 degrees or a vanishing gradient.
+  fn medial_axis(&self, distances: &SizeGrid) -> Vec<Point> {
+    let grads: Vec<Point> = (0..distances.ny)
+      .flat_map(|j| (0..distances.nx).map(move |i| (i, j)))
+      .map(|(i, j)| self.dist_fn.grad(&distances.node(i, j)))
+      .collect();
+
+    let mut medial_axis = Vec::new();
+    for j in 0..distances.ny {
+      for i in 0..distances.nx {
+        for (k, l) in [(i + 1, j), (i, j + 1)] {
+          if k >= distances.nx || l >= distances.ny || distances.value(i, j) >= 0.0 || distances.value(k, l) >= 0.0 {
+            continue;
+          }
+          let g1 = &grads[j * distances.nx + i];
+          let g2 = &grads[l * distances.nx + k];
+          if g1.x * g2.x + g1.y * g2.y <= std::f64::consts::FRAC_1_SQRT_2 * g1.len() * g2.len() {
+            // intersect the linear approximations of the distance at both nodes along the edge
+            let (u, v) = (distances.node(i, j), distances.node(k, l));
+            let dir = v.subtract(&u).norm();
+            let (a1, a2) = (g1.x * dir.x + g1.y * dir.y, g2.x * dir.x + g2.y * dir.y);
+            let t = if a1 != a2 {
+              ((distances.value(k, l) - a2 * distances.cell_size - distances.value(i, j)) / (a1 - a2)).clamp(0.0, distances.cell_size)
+            } else {
+              distances.cell_size / 2.0
+            };
+            medial_axis.push(u.add(&dir.mult(t)));
+          }
+        }
+      }
+    }
+    medial_axis
+  }
+}
+
+/// Returns the curvature of the level set of ```dist_fn``` through ```p```, computed from its gradient and Hessian.
+fn curvature(dist_fn: &dyn SignedDistanceFunction, p: &Point, epsilon: f64) -> f64 {
+  let d = |dx: f64, dy: f64| dist_fn.distance(&Point {x: p.x + dx * epsilon, y: p.y + dy * epsilon});
+  let dx = (d(1.0, 0.0) - d(-1.0, 0.0)) / (2.0 * epsilon);
+  let dy = (d(0.0, 1.0) - d(0.0, -1.0)) / (2.0 * epsilon);
+  let (dxx, dxy, dyy) = dist_fn.hessian_with_eps(p, epsilon);
+  let len_sq = dx * dx + dy * dy;
+  if len_sq == 0.0 {
+    return 0.0;
+  }
+  (dxx * dy * dy - 2.0 * dx * dy * dxy + dyy * dx * dx) / len_sq.powf(1.5)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::sfd::{Circle, Rect};
+
+  #[test]
+  fn test_size_grid_interpolation() {
+    let bounding_box = BoundigBox {x: -1.0, y: -1.0, w: 2.0, h: 2.0};
+    let grid = SizeGrid::from_fn(bounding_box, 0.1, |p| 1.0 + 2.0 * p.x - p.y);
+    for p in [Point {x: 0.03, y: -0.57}, Point {x: -1.0, y: 1.0}, Point {x: 0.999, y: 0.111}] {
+      assert!((grid.size(&p) - (1.0 + 2.0 * p.x - p.y)).abs() < 1e-10);
+    }
+    // outside the grid the closest node is used
+    assert!((grid.size(&Point {x: 2.0, y: 0.0}) - 3.0).abs() < 1e-10);
+  }
+
+  #[test]
+  fn test_limit_gradient() {
+    let point_source = |p: &Point| if p.x.abs() < 1e-6 && p.y.abs() < 1e-6 { 0.1 } else { 10.0 };
+    let grid = SizeGrid::sample(&point_source, -1.0, -1.0, 1.0, 1.0, 100).limit_gradient(0.5);
+
+    for j in 0..grid.ny {
+      for i in 0..grid.nx {
+        let h = grid.value(i, j);
+        let r = grid.node(i, j).len();
+        assert!(h <= point_source(&grid.node(i, j)));
+        // the first order upwind scheme overestimates the distance in diagonal directions, most close to the source
+        assert!(h >= 0.1 + 0.5 * r - 1e-10 && h <= 0.1 + 0.5 * r * 1.21, "{} {}", h, r);
+        if i + 1 < grid.nx {
+          assert!((grid.value(i + 1, j) - h).abs() <= 0.5 * grid.cell_size + 1e-10);
+        }
+        if j + 1 < grid.ny {
+          assert!((grid.value(i, j + 1) - h).abs() <= 0.5 * grid.cell_size + 1e-10);
+        }
+      }
+    }
+
+    // sizes which already satisfy the gradation remain unchanged
+    let grid = SizeGrid::sample(&|p: &Point| 1.0 + 0.2 * p.x, -1.0, -1.0, 1.0, 1.0, 20).limit_gradient(0.5);
+    assert!((grid.size(&Point {x: 0.3, y: 0.7}) - 1.06).abs() < 1e-10);
+  }
+
+  #[test]
+  fn test_curvature() {
+    let circle = Circle::new(Point {x: 1.0, y: 0.0}, 2.0);
+    assert!((curvature(&circle, &Point {x: 3.0, y: 0.0}, 0.01) - 0.5).abs() < 1e-3);
+    assert!((curvature(&circle, &Point {x: 1.0, y: -2.0}, 0.01) - 0.5).abs() < 1e-3);
+    let rect = Rect::new(Point {x: 0.0, y: 0.0}, 2.0, 2.0);
+    assert!(curvature(&rect, &Point {x: 1.0, y: 0.0}, 0.01).abs() < 1e-10);
+  }
+
+  #[test]
+  fn test_local_feature_size() {
+    // curvature dominates close to the boundary: 1 / (2 * 1/1)
+    let size = LocalFeatureSizeBuilder::new(Box::new(Circle::new(Point {x: 0.0, y: 0.0}, 1.0)))
+      .x1(-1.0).x2(1.0).y1(-1.0).y2(1.0)
+      .elements_per_radian(2.0)
+      .build();
+    assert!((size.size(&Point {x: 1.0, y: 0.0}) - 0.5).abs() < 0.02);
+    // the center is part of the medial axis, i.e. lfs = 1
+    assert!((size.size(&Point {x: 0.0, y: 0.0}) - 2.0 / 3.0).abs() < 0.05);
+
+    // the narrow part of a dumbbell is resolved finer than the wide parts
+    let dumbbell = crate::sfd::SDFUnion::new(vec![
+      Box::new(Rect::new(Point {x: -0.75, y: 0.0}, 0.5, 1.0)),
+      Box::new(Rect::new(Point {x: 0.75, y: 0.0}, 0.5, 1.0)),
+      Box::new(Rect::new(Point {x: 0.0, y: 0.0}, 1.0, 0.1)),
+    ]);
+    let size = LocalFeatureSizeBuilder::new(Box::new(dumbbell))
+      .x1(-1.0).x2(1.0).y1(-0.5).y2(0.5)
+      .build();
+    let narrow = size.size(&Point {x: 0.0, y: 0.0});
+    assert!((narrow - 2.0 * 0.05 / 3.0).abs() < 0.005, "{}", narrow);
+    assert!(size.size(&Point {x: -0.75, y: 0.0}) > 4.0 * narrow);
+  }
+}
@@ -26,7 +26,7 @@ fn faces_to_tikz(distmesh: &DistMesh) -> String {
   for index in 0..distmesh.triangulation.triangles.len()  {
     if index % 3 == 2 {
         tikz.push_str(&face_to_tikz_string(distmesh, index));
-        tikz.push_str("\n");
+        tikz.push('\n');
         //\filldraw[fill=faceColor1](0.8000,-0.7764)--(0.7408,-0.7396)--(0.7744,-0.7724)-- (0.8000,-0.7764);
     }
   }
@@ -107,7 +107,7 @@ fn edges_to_tikz(distmesh: &DistMesh) -> String {
   for &halfedge in &distmesh.triangulation.halfedges {
     if !distmesh.is_empty(halfedge) {
       tikz.push_str(&halfedge_to_tikz(distmesh, halfedge));
-      tikz.push_str("\n");
+      tikz.push('\n');
     }
   }
 
@@ -120,7 +120,7 @@ fn edges_to_tikz(distmesh: &DistMesh) -> String {
     let v: &Point = &distmesh.points[iv];
 
     tikz.push_str(&points_to_tikz(u, v));
-    tikz.push_str("\n");
+    tikz.push('\n');
   }
   tikz
 }
@@ -136,7 +136,7 @@ fn vertices_to_tikz(distmesh: &DistMesh) -> String {
     tikz.push_str("\\circleSize");
     tikz.push(')');
     tikz.push(';');
-    tikz.push_str("\n");
+    tikz.push('\n');
   }
 
   tikz
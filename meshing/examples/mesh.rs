@@ -1,6 +1,3 @@
-use meshing::geometry::Point;
-use meshing::geometry::equiliteral_triangle;
-
 pub fn main() {
   /*let (p1, p2, p3) = equiliteral_triangle(100.0);
   let mesh = Mesh::triangle(p1, p2, p3);
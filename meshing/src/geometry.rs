@@ -1,8 +1,9 @@
 use core::{f64, fmt};
-use robust::orient2d;
+use robust::{incircle, orient2d};
 
 pub const EPSILON: f64 = f64::EPSILON * 2.0;
 
+#[allow(clippy::len_without_is_empty)]
 pub trait DSPoint {
   /// Returns a new point which is equal to ```self``` - ```other```.
   fn subtract(&self, other: &Self) -> Self;
@@ -166,7 +167,7 @@ impl DSPoint for Point {
   }
 
   fn center(&self, other: &Self) -> Self {
-    let du = other.subtract(&self).mult(0.5);
+    let du = other.subtract(self).mult(0.5);
     self.add(&du)
   }
 
@@ -190,6 +191,10 @@ impl DSPoint for Point {
       }
   }
 
+  fn in_circle(&self, a: &Self, b: &Self, c: &Self) -> bool {
+    incircle(a.into(), b.into(), c.into(), self.into()) > 0.0
+  }
+
   fn orient(&self, q: &Self, r: &Self) -> f64 {
     orient2d(self.into(), q.into(), r.into())
   }
@@ -333,16 +338,16 @@ mod tests {
 
     #[test]
     fn test_calc_bbox_center() {
-      assert!(calc_bbox_center(&vec![Point{x:0.0, y:0.0}]).nearly_equals(&Point {x: 0.0, y:0.0}));
-      assert!(calc_bbox_center(&vec![Point{x:0.0, y:0.0}, Point{x:1.0, y:1.0}]).nearly_equals(&Point {x: 0.5, y:0.5}));
-      assert!(calc_bbox_center(&vec![Point{x:0.0, y:0.0}, Point{x:1.0, y:1.0}, Point{x:0.0, y:1.0}]).nearly_equals(&Point {x: 0.5, y:0.5}));
+      assert!(calc_bbox_center(&[Point{x:0.0, y:0.0}]).nearly_equals(&Point {x: 0.0, y:0.0}));
+      assert!(calc_bbox_center(&[Point{x:0.0, y:0.0}, Point{x:1.0, y:1.0}]).nearly_equals(&Point {x: 0.5, y:0.5}));
+      assert!(calc_bbox_center(&[Point{x:0.0, y:0.0}, Point{x:1.0, y:1.0}, Point{x:0.0, y:1.0}]).nearly_equals(&Point {x: 0.5, y:0.5}));
     }
 
     #[test]
     fn test_find_closest_point() {
-      assert!(find_closest_point(&vec![Point{x:0.0, y:0.0}], &Point{x:1.0, y:1.0}).unwrap() == 0);
-      assert!(find_closest_point(&vec![Point{x:0.0, y:0.0}, Point{x:0.5, y:0.5}], &Point{x:1.0, y:1.0}).unwrap() == 1);
-      assert!(find_closest_point(&vec![], &Point{x:1.0, y:1.0}) == None);
+      assert!(find_closest_point(&[Point{x:0.0, y:0.0}], &Point{x:1.0, y:1.0}).unwrap() == 0);
+      assert!(find_closest_point(&[Point{x:0.0, y:0.0}, Point{x:0.5, y:0.5}], &Point{x:1.0, y:1.0}).unwrap() == 1);
+      assert!(find_closest_point(&[], &Point{x:1.0, y:1.0}).is_none());
     }
   }
 
@@ -359,14 +364,14 @@ mod tests {
       let r3 = Point {x: 5.0, y: 0.0};
 
       assert!(u1.orient(&q, &r1) > 0.0);
-      assert!(u1.ccw(&q, &r1) == true);
+      assert!(u1.ccw(&q, &r1));
 
       assert!(u1.orient(&q, &r2) < 0.0);
-      assert!(u1.ccw(&q, &r2) == false);
+      assert!(!u1.ccw(&q, &r2));
 
       // edge case => colinear
       assert!(u1.orient(&q, &r3) == 0.0);
-      assert!(u1.ccw(&q, &r3) == false);
+      assert!(!u1.ccw(&q, &r3));
     }
 
     #[test]
@@ -405,12 +410,12 @@ mod tests {
       let u3 = Point {x: 0.0, y: 1.0};
       assert!(u1.ccw(&u2, &u3));
 
-      assert!(Point{x: EPSILON, y: EPSILON}.in_circle(&u1, &u2, &u3) == true);
-      assert!(Point{x: -EPSILON, y: -EPSILON}.in_circle(&u1, &u2, &u3) == false);
-      assert!(Point{x: 10.0, y: 10.0}.in_circle(&u1, &u2, &u3) == false);
-      assert!(Point{x: 0.5, y: 2.0}.in_circle(&u1, &u2, &u3) == false);
-      assert!(Point{x: -0.5, y: -2.0}.in_circle(&u1, &u2, &u3) == false);
-      assert!(Point{x: 0.5, y: 1.1}.in_circle(&u1, &u2, &u3) == true);
+      assert!(Point{x: EPSILON, y: EPSILON}.in_circle(&u1, &u2, &u3));
+      assert!(!Point{x: -EPSILON, y: -EPSILON}.in_circle(&u1, &u2, &u3));
+      assert!(!Point{x: 10.0, y: 10.0}.in_circle(&u1, &u2, &u3));
+      assert!(!Point{x: 0.5, y: 2.0}.in_circle(&u1, &u2, &u3));
+      assert!(!Point{x: -0.5, y: -2.0}.in_circle(&u1, &u2, &u3));
+      assert!(Point{x: 0.5, y: 1.1}.in_circle(&u1, &u2, &u3));
     }
   }
 }
\ No newline at end of file
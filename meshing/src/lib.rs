@@ -1,4 +1,9 @@
 pub mod mesh;
 pub mod sfd;
 pub mod tikz;
-pub mod geometry;
\ No newline at end of file
+pub mod geometry;
+pub mod triangulator;
+pub mod refinement;
+
+#[cfg(test)]
+mod testing;
\ No newline at end of file
@@ -1,7 +1,13 @@
+use core::fmt;
+use std::collections::VecDeque;
+use delaunator::{next_halfedge, Triangulation};
 use crate::geometry::{DSPoint, Point};
 
 pub const EMPTY: usize = usize::MAX;
 
+/// Minimal ratio between twice the area of an ear and the squared length of its base such that it is used to fill the border.
+const FLAT_EAR: f64 = 1e-10;
+
 pub struct FaceIterator<'a> {
   mesh: &'a Mesh,
   face: usize,
@@ -11,7 +17,7 @@ pub struct FaceIterator<'a> {
 
 impl<'a> FaceIterator<'a> {
   pub fn empty(mesh: &'a Mesh) -> Self {
-    FaceIterator {mesh: mesh, face: EMPTY, start: EMPTY, current: EMPTY}
+    FaceIterator {mesh, face: EMPTY, start: EMPTY, current: EMPTY}
   }
 }
 
@@ -25,6 +31,9 @@ impl<'a> Iterator for FaceIterator<'a> {
 
     if self.start == EMPTY {
       self.start = self.mesh.faces[self.face].halfedge;
+      if self.start == EMPTY {
+        return None;
+      }
       self.current = self.start;
     } else {
       self.current = self.mesh.halfedges[self.current].next;
@@ -32,7 +41,7 @@ impl<'a> Iterator for FaceIterator<'a> {
         return None;
       }
     }
-    return Some(self.current);
+    Some(self.current)
   }
 }
 
@@ -60,8 +69,8 @@ impl<'a> HalfedgeIterator<'a> {
 
     HalfedgeIterator {
       mesh,
-      unvisited_faces: unvisited_faces,
-      visited_faces: visited_faces,
+      unvisited_faces,
+      visited_faces,
       face_iterator,
     }
   }
@@ -85,12 +94,7 @@ impl<'a> Iterator for HalfedgeIterator<'a> {
         }
 
         loop {
-          let face = self.unvisited_faces.pop();
-          if face.is_none() {
-            return None;
-          }
-
-          let face = face.unwrap();
+          let face = self.unvisited_faces.pop()?;
           if self.visited_faces[face] {
             continue;
           }
@@ -113,19 +117,15 @@ impl<'a> Iterator for FacesIterator<'a> {
   type Item = usize;
   
   fn next(&mut self) -> Option<Self::Item> {
-    if self.index >= self.mesh.faces.len() {
-      return None;
-    }
-
-    while self.mesh.faces[self.index].face_type != FaceType::Normal {
+    while self.index < self.mesh.faces.len() && self.mesh.faces[self.index].face_type != FaceType::Normal {
       self.index += 1;
     }
 
     if self.index >= self.mesh.faces.len() {
-      return None;
+      None
     } else {
       self.index += 1;
-      return Some(self.mesh.faces[self.index-1].id);
+      Some(self.mesh.faces[self.index-1].id)
     }
   }
 }
@@ -139,16 +139,196 @@ impl<'a> Iterator for VertexIterator<'a> {
   type Item = usize;
   
   fn next(&mut self) -> Option<Self::Item> {
+    while self.index < self.mesh.vertices.len() && self.mesh.vertices[self.index].destroyed {
+      self.index += 1;
+    }
+
     if self.index >= self.mesh.vertices.len() {
       return None;
     }
 
     self.index += 1;
-    return Some(self.mesh.vertices[self.index-1].id);
+    Some(self.mesh.vertices[self.index-1].id)
+  }
+}
+
+/// Circulates clockwise around a vertex and yields all halfedges ending at the vertex, see [`Mesh::iter_incoming_edges`].
+pub struct VertexRingIterator<'a> {
+  mesh: &'a Mesh,
+  start: usize,
+  current: usize,
+}
+
+impl<'a> Iterator for VertexRingIterator<'a> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.start == EMPTY {
+      return None;
+    }
+
+    if self.current == EMPTY {
+      self.current = self.start;
+    } else {
+      self.current = self.mesh.twin(self.mesh.next(self.current));
+      if self.current == self.start {
+        return None;
+      }
+    }
+    Some(self.current)
+  }
+}
+
+/// The result of locating a point within a [`Mesh`], see [`Mesh::locate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+  /// The point lies strictly inside the face.
+  InFace(usize),
+  /// The point lies on the edge of the halfedge (but not on one of its vertices).
+  OnEdge(usize),
+  /// The point coincides with the vertex.
+  OnVertex(usize),
+  /// The point lies outside of the mesh and the border halfedge is visible from the point.
+  Outside(usize),
+}
+
+/// The reason why [`Mesh::collapse_edge`] rejected a collapse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollapseError {
+  /// The collapse would produce a non-manifold mesh (the link condition is violated).
+  LinkCondition,
+  /// Both vertices are part of the border but the edge is not, i.e. the collapse would pinch the mesh.
+  Boundary,
+  /// The collapse would invert at least one triangle.
+  Inversion,
+}
+
+impl fmt::Display for CollapseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CollapseError::LinkCondition => write!(f, "the collapse violates the link condition"),
+      CollapseError::Boundary => write!(f, "the collapse would connect two border vertices by an inner edge"),
+      CollapseError::Inversion => write!(f, "the collapse would invert a triangle"),
+    }
+  }
+}
+
+impl std::error::Error for CollapseError {}
+
+/// A violated invariant of a [`Mesh`] found by [`Mesh::check`]. Each variant contains the index of the offending element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshError {
+  /// The id of the face differs from its index.
+  FaceId(usize),
+  /// The id of the halfedge differs from its index.
+  HalfedgeId(usize),
+  /// The id of the vertex differs from its index.
+  VertexId(usize),
+  /// The face references a destroyed or non-existing halfedge.
+  DanglingFace(usize),
+  /// The halfedge references a destroyed or non-existing element.
+  DanglingHalfedge(usize),
+  /// The vertex references a destroyed or non-existing halfedge.
+  DanglingVertex(usize),
+  /// The twin of the twin is not the halfedge itself or the twin does not connect the same vertices in opposite direction.
+  Twin(usize),
+  /// ```prev(next(h))``` or ```next(prev(h))``` is not the halfedge ```h``` itself.
+  NextPrev(usize),
+  /// The halfedge is not part of the (single) cycle of its face.
+  FaceCycle(usize),
+  /// The halfedge of the vertex does not end at the vertex.
+  VertexHalfedge(usize),
+  /// The face is not oriented correctly, i.e. a normal or hole face is not ccw or the boundary face is not cw.
+  Orientation(usize),
+  /// The neighbourhood of the vertex is not a single (possibly open) fan.
+  NonManifoldVertex(usize),
+  /// The Euler characteristic V - E + F (counting normal faces only) is not equal to 1 - #holes.
+  EulerCharacteristic { expected: i64, actual: i64 },
+  /// The edge of the halfedge is not locally Delaunay.
+  NotDelaunay(usize),
+}
+
+impl fmt::Display for MeshError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      MeshError::FaceId(face) => write!(f, "face {} has a different id", face),
+      MeshError::HalfedgeId(halfedge) => write!(f, "halfedge {} has a different id", halfedge),
+      MeshError::VertexId(vertex) => write!(f, "vertex {} has a different id", vertex),
+      MeshError::DanglingFace(face) => write!(f, "face {} references an invalid halfedge", face),
+      MeshError::DanglingHalfedge(halfedge) => write!(f, "halfedge {} references an invalid element", halfedge),
+      MeshError::DanglingVertex(vertex) => write!(f, "vertex {} references an invalid halfedge", vertex),
+      MeshError::Twin(halfedge) => write!(f, "halfedge {} and its twin are inconsistent", halfedge),
+      MeshError::NextPrev(halfedge) => write!(f, "next and prev of halfedge {} are inconsistent", halfedge),
+      MeshError::FaceCycle(halfedge) => write!(f, "halfedge {} is not part of the cycle of its face", halfedge),
+      MeshError::VertexHalfedge(vertex) => write!(f, "the halfedge of vertex {} does not end at the vertex", vertex),
+      MeshError::Orientation(face) => write!(f, "face {} is not oriented correctly", face),
+      MeshError::NonManifoldVertex(vertex) => write!(f, "vertex {} is not manifold", vertex),
+      MeshError::EulerCharacteristic { expected, actual } => write!(f, "the Euler characteristic is {} instead of {}", actual, expected),
+      MeshError::NotDelaunay(halfedge) => write!(f, "the edge of halfedge {} is not locally Delaunay", halfedge),
+    }
   }
 }
 
-#[derive(Debug)]
+impl std::error::Error for MeshError {}
+
+/// The reason why [`Mesh::insert_constraint`] could not insert a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintError {
+  /// Both vertices are the same or one of them is not connected to the mesh.
+  Degenerate,
+  /// The segment crosses another constrained edge.
+  Crossing,
+  /// The segment leaves the mesh, i.e. it crosses the boundary or a hole.
+  Outside,
+}
+
+impl fmt::Display for ConstraintError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ConstraintError::Degenerate => write!(f, "the segment is degenerate"),
+      ConstraintError::Crossing => write!(f, "the segment crosses another constrained edge"),
+      ConstraintError::Outside => write!(f, "the segment leaves the mesh"),
+    }
+  }
+}
+
+impl std::error::Error for ConstraintError {}
+
+/// The reason why a hole could not be carved, see [`Mesh::carve_hole`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoleError {
+  /// The seed point does not lie strictly inside the mesh.
+  Outside,
+  /// The region is not enclosed by constrained edges, i.e. it touches the boundary or another hole.
+  Border,
+  /// The region is not simply connected or its border touches itself.
+  NotSimple,
+  /// The polygon enclosing the hole could not be inserted.
+  Constraint(ConstraintError),
+}
+
+impl fmt::Display for HoleError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      HoleError::Outside => write!(f, "the seed point lies outside of the mesh"),
+      HoleError::Border => write!(f, "the hole touches the border of the mesh"),
+      HoleError::NotSimple => write!(f, "the hole is not simply connected"),
+      HoleError::Constraint(error) => write!(f, "the polygon of the hole could not be inserted: {}", error),
+    }
+  }
+}
+
+impl std::error::Error for HoleError {}
+
+/// Maps old to new indices of a [`Mesh`] after [`Mesh::compact`]. Removed elements are mapped to [`EMPTY`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexMaps {
+  pub faces: Vec<usize>,
+  pub halfedges: Vec<usize>,
+  pub vertices: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Mesh {
   faces: Vec<Face>,
   holes: Vec<usize>,
@@ -163,46 +343,260 @@ impl Mesh {
     //TODO we assume ccw ordering here!
     debug_assert!(u1.ccw(&u2, &u3));
     let mut mesh = Self::empty();
-    let inner_face = mesh.create_face(FaceType::Normal);
     let v1 = mesh.create_vertex(u1);
     let v2 = mesh.create_vertex(u2);
     let v3 = mesh.create_vertex(u3);
+    mesh.create_triangle(v1, v2, v3);
+    mesh
+  }
 
-    let halfedge1 = mesh.create_halfedge(v1, Some(inner_face));
-    let halfedge2 = mesh.create_halfedge(v2, Some(inner_face));
-    let halfedge3 = mesh.create_halfedge(v3, Some(inner_face));
-    mesh.set_edge_of_vertex(v1, halfedge1);
-    mesh.set_edge_of_vertex(v2, halfedge2);
-    mesh.set_edge_of_vertex(v3, halfedge3);
-    mesh.set_edge_of_face(inner_face, halfedge1);
-
-    let twin1 = mesh.create_halfedge(v3, Some(mesh.boundary));
-    let twin2 = mesh.create_halfedge(v1, Some(mesh.boundary));
-    let twin3 = mesh.create_halfedge(v2, Some(mesh.boundary));
-    mesh.set_edge_of_face(mesh.boundary, twin1);
-    
-    mesh.set_cycle_and_twins(halfedge1, halfedge2, halfedge3, twin1, twin2, twin3);
+  /// Builds the mesh of a ```triangulation``` computed by ```delaunator``` (see [`Mesh::from_triangles`]).
+  /// Only ```triangulation.triangles``` is used, therefore, triangles may have been removed without updating
+  /// ```halfedges``` and ```hull```. The vertex with id ```i``` corresponds to ```points[i]```.
+  pub fn from_triangulation(points: &[delaunator::Point], triangulation: &Triangulation) -> Mesh {
+    let points: Vec<Point> = points.iter().map(|p| Point {x: p.x, y: p.y}).collect();
+    let triangles = &triangulation.triangles;
+    if triangulation.halfedges.len() != triangles.len() {
+      return Self::from_triangles(&points, triangles);
+    }
+
+    // an unmodified triangulation links its twins already: the halfedge e of delaunator becomes the reversed halfedge e,
+    // since the triangles of delaunator are cw
+    let mut mesh = Self::empty();
+    for p in points.into_iter() {
+      mesh.create_vertex(p);
+    }
+
+    for triangle in triangles.chunks_exact(3) {
+      let face = mesh.create_face(FaceType::Normal);
+      let halfedges = [mesh.create_halfedge(triangle[0], Some(face)), mesh.create_halfedge(triangle[1], Some(face)), mesh.create_halfedge(triangle[2], Some(face))];
+      mesh.set_cycle(halfedges[0], halfedges[2], halfedges[1]);
+      mesh.set_edge_of_face(face, halfedges[2]);
+      for (&h, &v) in halfedges.iter().zip(triangle) {
+        mesh.set_edge_of_vertex(v, h);
+      }
+    }
+
+    let mut border = Vec::new();
+    for (h, &twin) in triangulation.halfedges.iter().enumerate() {
+      if twin != EMPTY {
+        mesh.set_twin(h, twin);
+      } else {
+        let twin = mesh.create_halfedge(triangles[next_halfedge(h)], None);
+        mesh.set_twins(h, twin);
+        border.push(twin);
+      }
+    }
+
+    mesh.link_border(&border);
+    // start the boundary at the first hull vertex such that to_triangulation reproduces the hull
+    let start = triangulation.hull.first().and_then(|&v| mesh.iter_face(mesh.boundary).find(|&h| mesh.vertex(h) == v));
+    if let Some(h) = start {
+      mesh.set_edge_of_face(mesh.boundary, h);
+    }
+    mesh
+  }
+
+  /// Builds the mesh consisting of ```triangles```, i.e. triples of indices of ```points```, in any orientation.
+  /// The vertex with id ```i``` corresponds to ```points[i]```, unused points remain unconnected vertices.
+  /// Each border loop surrounding the triangles becomes part of the boundary face and each border loop surrounded by the triangles
+  /// becomes a hole face. The triangles have to form a manifold, i.e. each edge is shared by at most two triangles.
+  /// If they form multiple components, the border loop enclosing the largest area is used as the halfedge of the boundary face.
+  pub fn from_triangles(points: &[Point], triangles: &[usize]) -> Mesh {
+    let mut mesh = Self::empty();
+    for p in points.iter() {
+      mesh.create_vertex(p.clone());
+    }
+
+    for triangle in triangles.chunks_exact(3) {
+      let (a, mut b, mut c) = (triangle[0], triangle[1], triangle[2]);
+      if points[a].orient(&points[b], &points[c]) < 0.0 {
+        std::mem::swap(&mut b, &mut c);
+      }
+      let face = mesh.create_face(FaceType::Normal);
+      let halfedges = [mesh.create_halfedge(b, Some(face)), mesh.create_halfedge(c, Some(face)), mesh.create_halfedge(a, Some(face))];
+      mesh.set_cycle(halfedges[0], halfedges[1], halfedges[2]);
+      mesh.set_edge_of_face(face, halfedges[0]);
+      for (&h, v) in halfedges.iter().zip([b, c, a]) {
+        mesh.set_edge_of_vertex(v, h);
+      }
+    }
+
+    // halfedges of the same edge are neighbours after sorting by their (undirected) edge
+    let mut edges: Vec<(usize, usize, usize)> = (0..mesh.halfedges.len()).map(|h| {
+      let (u, v) = (mesh.vertex(mesh.prev(h)), mesh.vertex(h));
+      (u.min(v), u.max(v), h)
+    }).collect();
+    edges.sort_unstable();
+
+    let mut border = Vec::new();
+    let mut i = 0;
+    while i < edges.len() {
+      let (u, v, h) = edges[i];
+      if i + 1 < edges.len() && edges[i + 1].0 == u && edges[i + 1].1 == v {
+        debug_assert!(i + 2 >= edges.len() || edges[i + 2].0 != u || edges[i + 2].1 != v, "the edge ({}, {}) is part of more than two triangles", u, v);
+        mesh.set_twins(h, edges[i + 1].2);
+        i += 2;
+      } else {
+        let twin = mesh.create_halfedge(mesh.vertex(mesh.prev(h)), None);
+        mesh.set_twins(h, twin);
+        border.push(twin);
+        i += 1;
+      }
+    }
+
+    mesh.link_border(&border);
     mesh
   }
 
-  pub fn iter_vertices(&self) -> VertexIterator {
+  /// Links the ```border``` halfedges, i.e. the twins of all halfedges without a neighbouring triangle, to border loops.
+  /// Loops in cw order become part of the boundary face and loops in ccw order become holes.
+  fn link_border(&mut self, border: &[usize]) {
+    // the successor of a border halfedge is found by rotating around its end vertex until the border is reached
+    for &h in border.iter() {
+      let mut g = self.twin(h);
+      while self.face(self.twin(self.prev(g))) != EMPTY {
+        g = self.twin(self.prev(g));
+      }
+      let next = self.twin(self.prev(g));
+      self.set_next(h, next);
+      self.set_prev(next, h);
+    }
+
+    // cw loops belong to the boundary, ccw loops are holes
+    let mut loops: Vec<(f64, Vec<usize>)> = Vec::new();
+    for &start in border.iter() {
+      if self.face(start) != EMPTY {
+        continue;
+      }
+      let mut cycle = Vec::new();
+      let mut area = 0.0;
+      let mut h = start;
+      loop {
+        let p = self.point_of_edge(self.prev(h));
+        let q = self.point_of_edge(h);
+        area += p.x * q.y - q.x * p.y;
+        self.set_face(h, self.boundary);
+        cycle.push(h);
+        h = self.next(h);
+        if h == start {
+          break;
+        }
+      }
+      loops.push((area, cycle));
+    }
+
+    let mut max_area = 0.0;
+    for (area, cycle) in loops {
+      if area < 0.0 {
+        if -area > max_area {
+          max_area = -area;
+          self.set_edge_of_face(self.boundary, cycle[0]);
+        }
+      } else {
+        let hole = self.create_face(FaceType::Hole);
+        self.set_edge_of_face(hole, cycle[0]);
+        for h in cycle {
+          self.set_face(h, hole);
+        }
+      }
+    }
+  }
+
+  /// Returns the triangles of the mesh as triples of vertex ids in ccw order.
+  /// If the mesh contains destroyed elements, [`Mesh::compact`] should be called first.
+  pub fn to_triangles(&self) -> Vec<usize> {
+    let mut triangles = Vec::with_capacity(3 * self.faces.len());
+    for face in self.iter_faces() {
+      let h = self.edge_of_face(face);
+      triangles.extend([self.vertex(self.prev(h)), self.vertex(h), self.vertex(self.next(h))]);
+    }
+    triangles
+  }
+
+  /// Returns the mesh as [`Triangulation`] using the conventions of ```delaunator```, i.e. the orientation of the triangles is
+  /// reversed, ```halfedges``` links neighbouring triangles and ```hull``` contains the vertices of the boundary.
+  /// For an unmodified mesh built by [`Mesh::from_triangulation`] this is the inverse operation.
+  pub fn to_triangulation(&self) -> Triangulation {
+    let faces: Vec<usize> = self.iter_faces().collect();
+    let mut index = vec![EMPTY; self.halfedges.len()];
+    let mut triangles = Vec::with_capacity(3 * faces.len());
+    for (i, &face) in faces.iter().enumerate() {
+      let h = self.edge_of_face(face);
+      triangles.extend([self.vertex(self.prev(h)), self.vertex(self.next(h)), self.vertex(h)]);
+      index[self.prev(h)] = 3 * i;
+      index[self.next(h)] = 3 * i + 1;
+      index[h] = 3 * i + 2;
+    }
+
+    let mut halfedges = vec![EMPTY; triangles.len()];
+    for (h, &i) in index.iter().enumerate() {
+      if i != EMPTY {
+        halfedges[i] = index[self.twin(h)];
+      }
+    }
+    let hull = self.iter_face(self.boundary).map(|h| self.vertex(h)).collect();
+    Triangulation {triangles, halfedges, hull}
+  }
+
+  pub fn iter_vertices(&self) -> VertexIterator<'_> {
     VertexIterator {mesh: self, index: 0}
   }
 
-  pub fn iter_faces(&self) -> FacesIterator {
+  pub fn iter_faces(&self) -> FacesIterator<'_> {
     FacesIterator {mesh: self, index: 0}
   }
 
-  pub fn iter_face<'a>(&'a self, face: usize) -> FaceIterator {
-    FaceIterator {mesh: self, face: face, start: EMPTY, current: EMPTY}
+  pub fn iter_face(&self, face: usize) -> FaceIterator<'_> {
+    FaceIterator {mesh: self, face, start: EMPTY, current: EMPTY}
   }
 
-  pub fn iter_edges<'a>(&'a self) -> HalfedgeIterator {
+  pub fn iter_edges(&self) -> HalfedgeIterator<'_> {
     HalfedgeIterator::new(self)
   }
 
+  /// Returns an iterator over all halfedges ending at ```vertex``` in clockwise order. An isolated vertex has no halfedges.
+  pub fn iter_incoming_edges(&self, vertex: usize) -> VertexRingIterator<'_> {
+    VertexRingIterator {mesh: self, start: self.edge_of_vertex(vertex), current: EMPTY}
+  }
+
+  /// Returns an iterator over all halfedges starting at ```vertex``` in clockwise order.
+  pub fn iter_outgoing_edges(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+    self.iter_incoming_edges(vertex).map(move |halfedge| self.twin(halfedge))
+  }
+
+  /// Returns an iterator over all vertices connected to ```vertex``` by an edge in clockwise order.
+  pub fn iter_adjacent_vertices(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+    self.iter_outgoing_edges(vertex).map(move |halfedge| self.vertex(halfedge))
+  }
+
+  /// Returns an iterator over all normal faces containing ```vertex``` in clockwise order.
+  /// For a vertex on the border, the boundary (or hole) face is skipped.
+  pub fn iter_incident_faces(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+    self.iter_incoming_edges(vertex).map(move |halfedge| self.face(halfedge)).filter(move |&face| self.is_normal(face))
+  }
+
+  /// Returns the number of edges connected to ```vertex```.
+  pub fn valence(&self, vertex: usize) -> usize {
+    self.iter_incoming_edges(vertex).count()
+  }
+
+  /// Returns **true** if and only if ```vertex``` is part of the boundary or of a hole.
+  pub fn is_boundary_vertex(&self, vertex: usize) -> bool {
+    self.iter_incoming_edges(vertex).any(|halfedge| !self.is_normal(self.face(halfedge)))
+  }
+
+  /// Inserts the point ```p``` outside of the mesh by connecting it with the border ```halfedge``` which has to be visible from ```p```.
+  /// Returns the new border halfedge ending at the new vertex.
   pub fn insert(&mut self, halfedge: usize, p: Point) -> usize {
+    let v = self.create_vertex(p);
+    self.insert_vertex(halfedge, v)
+  }
+
+  /// Same as [`Mesh::insert`] but for an already existing but not yet connected ```vertex```.
+  pub(crate) fn insert_vertex(&mut self, halfedge: usize, v: usize) -> usize {
     debug_assert!(self.halfedges.len() > halfedge);
+    debug_assert!(self.vertices[v].halfedge == EMPTY);
     debug_assert!(
       self.faces[self.face(halfedge)].face_type == FaceType::Boundary || 
       self.faces[self.face(halfedge)].face_type == FaceType::Hole
@@ -217,7 +611,6 @@ impl Mesh {
     }
 
     let face = self.create_face(FaceType::Normal);
-    let v = self.create_vertex(p);
 
     self.set_edge_of_face(face, halfedge);
     self.set_face_of_edge(halfedge, face);
@@ -251,31 +644,515 @@ impl Mesh {
     t2
   }
 
+  /// Closes the concave corner of the border formed by ```halfedge``` and its successor by a new triangle.
+  /// Both halfedges have to be part of the boundary (or a hole) and have to turn to the left, i.e. the end of the successor has to be visible from
+  /// the start of ```halfedge```. Returns the new border halfedge which replaces both. If the corner belongs to a triangular hole,
+  /// the hole is filled, i.e. it becomes a normal face, and [`EMPTY`] is returned.
+  pub fn connect(&mut self, halfedge: usize) -> usize {
+    debug_assert!(self.halfedges.len() > halfedge);
+    debug_assert!(
+      self.faces[self.face(halfedge)].face_type == FaceType::Boundary || 
+      self.faces[self.face(halfedge)].face_type == FaceType::Hole
+    );
+
+    let a = self.prev(halfedge);
+    let n = self.next(halfedge);
+    let b = self.next(n);
+    let border = self.face(halfedge);
+    debug_assert!(self.point_of_edge(a).ccw(self.point_of_edge(halfedge), self.point_of_edge(n)));
+
+    if a == b {
+      debug_assert!(self.faces[border].face_type == FaceType::Hole);
+      self.faces[border].face_type = FaceType::Normal;
+      self.holes.retain(|&hole| hole != border);
+      return EMPTY;
+    }
+
+    if self.edge_of_face(border) == halfedge || self.edge_of_face(border) == n {
+      self.set_edge_of_face(border, b);
+    }
+
+    let face = self.create_face(FaceType::Normal);
+    self.set_edge_of_face(face, halfedge);
+    self.set_face_of_edge(halfedge, face);
+    self.set_face_of_edge(n, face);
+
+    let e = self.create_halfedge(self.vertex(a), Some(face));
+    let t = self.create_halfedge(self.vertex(n), Some(border));
+    self.set_twins(e, t);
+    self.set_cycle(halfedge, n, e);
+
+    self.set_next(a, t);
+    self.set_next(t, b);
+    self.set_prev(b, t);
+    self.set_prev(t, a);
+
+    t
+  }
+
+  /// Inserts the point ```p``` inside the (triangular) ```face``` by splitting it into three triangles (1 to 3 split).
+  /// Afterwards the Delaunay property is restored by Lawson flips. Returns the new vertex.
+  pub fn split_face(&mut self, face: usize, p: Point) -> usize {
+    debug_assert!(self.is_normal(face));
+    let v = self.create_vertex(p);
+
+    //             c
+    //            /|\
+    //        h2 / | \ h1
+    //          /  v  \
+    //         / /   \ \
+    //        a ------- b
+    //            h0
+    //
+    let h0 = self.edge_of_face(face);
+    let h1 = self.next(h0);
+    let h2 = self.next(h1);
+    debug_assert!(self.next(h2) == h0);
+
+    let a = self.vertex(h2);
+    let b = self.vertex(h0);
+    let c = self.vertex(h1);
+    debug_assert!(self.point_of_vertex(a).ccw(self.point_of_vertex(b), self.point_of_vertex(v)));
+    debug_assert!(self.point_of_vertex(b).ccw(self.point_of_vertex(c), self.point_of_vertex(v)));
+    debug_assert!(self.point_of_vertex(c).ccw(self.point_of_vertex(a), self.point_of_vertex(v)));
+
+    let f1 = self.create_face(FaceType::Normal);
+    let f2 = self.create_face(FaceType::Normal);
+
+    let x0 = self.create_halfedge(v, Some(face));
+    let y0 = self.create_halfedge(b, Some(f1));
+    let x1 = self.create_halfedge(v, Some(f1));
+    let y1 = self.create_halfedge(c, Some(f2));
+    let x2 = self.create_halfedge(v, Some(f2));
+    let y2 = self.create_halfedge(a, Some(face));
+
+    self.set_twins(x0, y0);
+    self.set_twins(x1, y1);
+    self.set_twins(x2, y2);
+
+    self.set_face(h1, f1);
+    self.set_face(h2, f2);
+    self.set_edge_of_face(f1, h1);
+    self.set_edge_of_face(f2, h2);
+
+    self.set_cycle(h0, x0, y2);
+    self.set_cycle(h1, x1, y0);
+    self.set_cycle(h2, x2, y1);
+    self.set_edge_of_vertex(v, x0);
+
+    self.legalize(h0);
+    self.legalize(h1);
+    self.legalize(h2);
+    v
+  }
+
+  /// Inserts the point ```p``` on the edge of ```halfedge``` by splitting the edge into two and each of its adjacent triangles into two (2 to 4 split).
+  /// If the edge is part of the border, only one triangle is split. 
+  /// Afterwards the Delaunay property is restored by Lawson flips. Returns the new vertex.
+  pub fn split_edge(&mut self, halfedge: usize, p: Point) -> usize {
+    let v = self.create_vertex(p);
+    self.split_edge_at_vertex(halfedge, v);
+    v
+  }
+
+  /// Same as [`Mesh::split_edge`] but for an already existing but not yet connected ```vertex```.
+  pub(crate) fn split_edge_at_vertex(&mut self, halfedge: usize, v: usize) {
+    debug_assert!(self.vertices[v].halfedge == EMPTY);
+
+    //  a ---- h ---> b      a - h -> v - h' -> b
+    //  a <--- t ---- b  =>  a <- t' - v <- t -- b
+    let h = halfedge;
+    let t = self.twin(h);
+    let a = self.vertex(t);
+    let b = self.vertex(h);
+    let constrained = self.is_constrained(h);
+
+    let h_next = self.next(h);
+    let t_next = self.next(t);
+    let h_split = self.create_halfedge(b, Some(self.face(h)));
+    let t_split = self.create_halfedge(a, Some(self.face(t)));
+
+    self.set_vertex(h, v);
+    self.set_vertex(t, v);
+    self.set_twins(h, t_split);
+    self.set_twins(t, h_split);
+    self.set_constrained(h, constrained);
+    self.set_constrained(h_split, constrained);
+
+    self.set_next(h, h_split);
+    self.set_prev(h_split, h);
+    self.set_next(h_split, h_next);
+    self.set_prev(h_next, h_split);
+
+    self.set_next(t, t_split);
+    self.set_prev(t_split, t);
+    self.set_next(t_split, t_next);
+    self.set_prev(t_next, t_split);
+
+    if self.edge_of_vertex(b) == h {
+      self.set_edge_of_vertex(b, h_split);
+    }
+    if self.edge_of_vertex(a) == t {
+      self.set_edge_of_vertex(a, t_split);
+    }
+    self.set_edge_of_vertex(v, h);
+
+    // the affected triangles became quadrilaterals, cut them by connecting v with the opposite vertex.
+    let mut legalize = Vec::with_capacity(4);
+    for e in [h, t] {
+      if self.is_normal(self.face(e)) {
+        let opposite = self.next(self.next(e));
+        self.insert_diagonal(e, opposite);
+        legalize.push(opposite);
+        legalize.push(self.prev(e));
+      }
+    }
+
+    for e in legalize {
+      self.legalize(e);
+    }
+  }
+
+  /// Removes the vertex ```v``` together with all its incident faces and retriangulates the resulting polygon such that the 
+  /// Delaunay property is preserved. If ```v``` is part of the border (of the boundary or a hole), the polygon is only filled as long as the new triangles 
+  /// neither cover the position of the removed vertex nor other vertices of the border, i.e. the border moves towards the neighbours of ```v```.
+  /// All removed elements are marked as destroyed.
+  pub fn remove_vertex(&mut self, v: usize) {
+    debug_assert!(!self.vertices[v].destroyed);
+    if self.edge_of_vertex(v) == EMPTY {
+      self.destroy_vertex(v);
+      return;
+    }
+
+    let removed = self.point_of_vertex(v).clone();
+    let incoming = self.incoming_edges(v);
+
+    // the border face next to v (if any) and the border halfedges surrounding the removed fan.
+    let border = incoming.iter().find(|&&h| !self.is_normal(self.face(h))).map(|&h| (self.face(h), self.prev(h), self.next(self.next(h))));
+    let target = border.map_or(self.face(incoming[0]), |(face, _, _)| face);
+
+    // every halfedge in front of an incoming halfedge is followed by the halfedge after the twin of the incoming halfedge
+    let links: Vec<(usize, usize)> = incoming.iter().map(|&h| (self.prev(h), self.next(self.twin(h)))).collect();
+    for &h in incoming.iter() {
+      let face = self.face(h);
+      if face != target {
+        self.destroy_face(face);
+      }
+    }
+
+    for (&h, &(a, b)) in incoming.iter().zip(links.iter()) {
+      let x = self.vertex(a);
+      if self.edge_of_vertex(x) == self.twin(h) {
+        self.set_edge_of_vertex(x, a);
+      }
+      self.set_next(a, b);
+      self.set_prev(b, a);
+      self.set_face(a, target);
+    }
+    self.set_edge_of_face(target, links[0].0);
+
+    for &h in incoming.iter() {
+      let twin = self.twin(h);
+      self.destroy_halfedge(h);
+      self.destroy_halfedge(twin);
+    }
+    self.destroy_vertex(v);
+
+    match border {
+      Some((_, before, after)) => self.fill_border(before, after, &removed),
+      None => self.triangulate_polygon(target),
+    }
+  }
+
+  /// Collapses the edge of ```halfedge``` by merging its start vertex into its end vertex. If only the start vertex is part of the border,
+  /// the end vertex is merged into the start vertex instead such that border vertices stay on the border. 
+  /// The collapse is rejected if it violates the link condition, if it would connect two parts of the border by an inner edge or if
+  /// it would invert a triangle. On success the surviving vertex is returned and all removed elements are marked as destroyed.
+  pub fn collapse_edge(&mut self, halfedge: usize) -> Result<usize, CollapseError> {
+    let (halfedge, u, w) = {
+      let u = self.vertex(self.twin(halfedge));
+      let w = self.vertex(halfedge);
+      match (self.is_boundary_vertex(u), self.is_boundary_vertex(w)) {
+        (true, true) if !self.is_border_edge(halfedge) => return Err(CollapseError::Boundary),
+        (true, false) => (self.twin(halfedge), w, u),
+        _ => (halfedge, u, w),
+      }
+    };
+    let twin = self.twin(halfedge);
+
+    // link condition: the common neighbours of u and w have to be exactly the vertices opposite of the edge
+    let opposite: Vec<usize> = [halfedge, twin].iter()
+      .filter(|&&e| self.is_normal(self.face(e)))
+      .map(|&e| self.vertex(self.next(e)))
+      .collect();
+    let neighbours_w: Vec<usize> = self.incoming_edges(w).iter().map(|&e| self.vertex(self.twin(e))).collect();
+    let common = self.incoming_edges(u).iter()
+      .map(|&e| self.vertex(self.twin(e)))
+      .filter(|x| neighbours_w.contains(x))
+      .count();
+    if common != opposite.len() || opposite.iter().any(|x| !neighbours_w.contains(x)) {
+      return Err(CollapseError::LinkCondition);
+    }
+
+    // a triangle with all of its edges on the border would degenerate to a dangling edge
+    for e in [halfedge, twin] {
+      if self.is_normal(self.face(e)) && self.iter_face(self.face(e)).all(|e| self.is_border_edge(e)) {
+        return Err(CollapseError::LinkCondition);
+      }
+    }
+
+    // no triangle may flip its orientation if u is moved to w
+    let target = self.point_of_vertex(w);
+    for h in self.incoming_edges(u) {
+      let face = self.face(h);
+      if !self.is_normal(face) || face == self.face(halfedge) || face == self.face(twin) {
+        continue;
+      }
+      let a = self.point_of_edge(self.prev(h));
+      let c = self.point_of_edge(self.next(h));
+      if a.orient(target, c) <= 0.0 {
+        return Err(CollapseError::Inversion);
+      }
+    }
+
+    let incoming_u = self.incoming_edges(u);
+    let mut destroyed = vec![halfedge, twin];
+    let mut survivor = EMPTY;
+    for e in [halfedge, twin] {
+      let face = self.face(e);
+      if self.is_normal(face) {
+        //   x         e1: w -> x, e2: x -> u
+        //  / \
+        // u - w
+        let e1 = self.next(e);
+        let e2 = self.prev(e);
+        let x = self.vertex(e1);
+        let o1 = self.twin(e1);
+        let o2 = self.twin(e2);
+        self.set_twins(o1, o2);
+        self.set_constrained(o1, self.is_constrained(o1) || self.is_constrained(o2));
+        survivor = o1;
+        if self.edge_of_vertex(x) == e1 {
+          self.set_edge_of_vertex(x, o2);
+        }
+        self.destroy_face(face);
+        destroyed.push(e1);
+        destroyed.push(e2);
+      } else {
+        let prev = self.prev(e);
+        let next = self.next(e);
+        self.set_next(prev, next);
+        self.set_prev(next, prev);
+        if self.edge_of_face(face) == e {
+          self.set_edge_of_face(face, next);
+        }
+      }
+    }
+
+    for h in incoming_u {
+      if !destroyed.contains(&h) {
+        self.set_vertex(h, w);
+      }
+    }
+    if destroyed.contains(&self.edge_of_vertex(w)) {
+      self.set_edge_of_vertex(w, survivor);
+    }
+
+    for h in destroyed {
+      self.destroy_halfedge(h);
+    }
+    self.destroy_vertex(u);
+    Ok(w)
+  }
+
+  /// Inserts the segment from ```v1``` to ```v2``` as a constrained edge. All edges crossing the segment are flipped away and the
+  /// Delaunay property is restored for the remaining new edges, i.e. a constrained Delaunay triangulation stays one.
+  /// If the segment passes through other vertices it is split into multiple constrained edges.
+  /// Returns the constrained halfedges from ```v1``` to ```v2``` in order. On failure, the parts of the segment which
+  /// have already been inserted stay constrained.
+  pub fn insert_constraint(&mut self, v1: usize, v2: usize) -> Result<Vec<usize>, ConstraintError> {
+    if v1 == v2 || self.edge_of_vertex(v1) == EMPTY || self.edge_of_vertex(v2) == EMPTY {
+      return Err(ConstraintError::Degenerate);
+    }
+
+    let mut constrained = Vec::new();
+    let mut a = v1;
+    while a != v2 {
+      let halfedge = self.recover_segment(a, v2)?;
+      self.set_constrained(halfedge, true);
+      a = self.vertex(halfedge);
+      constrained.push(halfedge);
+    }
+    Ok(constrained)
+  }
+
+  /// Carves a hole into the mesh (like the hole markers of Triangle): starting at the face containing ```seed```, all faces
+  /// which can be reached without crossing a constrained edge are merged into a new hole face. Vertices inside the hole are destroyed.
+  /// The region has to be enclosed by constrained edges, i.e. it must not touch the boundary or another hole, and it has to be simply connected.
+  /// Returns the new hole face whose border can be iterated by [`Mesh::iter_face`].
+  pub fn carve_hole(&mut self, seed: &Point) -> Result<usize, HoleError> {
+    let face = match self.locate(seed) {
+      Location::InFace(face) => face,
+      Location::OnEdge(halfedge) if self.is_normal(self.face(halfedge)) => self.face(halfedge),
+      _ => return Err(HoleError::Outside),
+    };
+    self.carve_region(face)
+  }
+
+  /// Inserts the closed polygon of ```vertices``` as constrained edges (see [`Mesh::insert_constraint`]) and carves the enclosed
+  /// region as a hole (see [`Mesh::carve_hole`]). The polygon may be given in ccw or cw order.
+  pub fn carve_polygon_hole(&mut self, vertices: &[usize]) -> Result<usize, HoleError> {
+    if vertices.len() < 3 {
+      return Err(HoleError::Constraint(ConstraintError::Degenerate));
+    }
+
+    let mut first = EMPTY;
+    for (i, &v) in vertices.iter().enumerate() {
+      let halfedges = self.insert_constraint(v, vertices[(i + 1) % vertices.len()]).map_err(HoleError::Constraint)?;
+      if i == 0 {
+        first = halfedges[0];
+      }
+    }
+
+    let area: f64 = vertices.iter().enumerate().map(|(i, &v)| {
+      let p = self.point_of_vertex(v);
+      let q = self.point_of_vertex(vertices[(i + 1) % vertices.len()]);
+      p.x * q.y - q.x * p.y
+    }).sum();
+    let inside = if area > 0.0 { first } else { self.twin(first) };
+    if !self.is_normal(self.face(inside)) {
+      return Err(HoleError::Border);
+    }
+    self.carve_region(self.face(inside))
+  }
+
+  pub fn holes(&self) -> &[usize] {
+    &self.holes
+  }
+
+  /// Returns **true** if and only if the edge of ```halfedge``` is constrained, see [`Mesh::insert_constraint`].
+  pub fn is_constrained(&self, halfedge: usize) -> bool {
+    debug_assert!(self.halfedges.len() > halfedge);
+    self.halfedges[halfedge].constrained
+  }
+
+  /// Moves ```vertex``` to ```p``` without changing the connectivity, therefore, faces might become non-Delaunay or even inverted.
+  pub fn move_vertex(&mut self, vertex: usize, p: Point) {
+    debug_assert!(self.vertices.len() > vertex);
+    self.vertices[vertex].point = p;
+  }
+
+  /// Returns all normal faces which are not in strict ccw order, e.g., after calling [`Mesh::move_vertex`].
+  pub fn inverted_faces(&self) -> Vec<usize> {
+    self.iter_faces().filter(|&face| {
+      let halfedge = self.edge_of_face(face);
+      let p0 = self.point_of_edge(self.prev(halfedge));
+      let p1 = self.point_of_edge(halfedge);
+      let p2 = self.point_of_edge(self.next(halfedge));
+      !p0.ccw(p1, p2)
+    }).collect()
+  }
+
+  /// Removes the triangle ```face``` which has exactly one edge on the border such that the face becomes part of the boundary or hole.
+  /// The vertex opposite of the border edge may not be part of the border, otherwise, nothing is changed and **false** is returned.
+  /// This allows to cut off inverted slivers along the border instead of triangulating all vertices again.
+  pub fn remove_border_face(&mut self, face: usize) -> bool {
+    debug_assert!(self.is_normal(face));
+    let Some(h0) = self.iter_face(face).find(|&halfedge| self.is_border(self.twin(halfedge))) else {
+      return false;
+    };
+    let h1 = self.next(h0);
+    let h2 = self.next(h1);
+    if self.next(h2) != h0 || self.is_border(self.twin(h1)) || self.is_border(self.twin(h2)) || self.is_boundary_vertex(self.vertex(h1)) {
+      return false;
+    }
+
+    //  border:  p --> t0 --> n          p --> h1 --> h2 --> n
+    //                 a <-- b                b --> c --> a
+    let t0 = self.twin(h0);
+    let border = self.face(t0);
+    let p = self.prev(t0);
+    let n = self.next(t0);
+    let a = self.vertex(t0);
+    let b = self.vertex(h0);
+    self.set_next(p, h1);
+    self.set_prev(h1, p);
+    self.set_next(h2, n);
+    self.set_prev(n, h2);
+    self.set_next(h1, h2);
+    self.set_prev(h2, h1);
+    self.set_face_of_edge(h1, border);
+    self.set_face_of_edge(h2, border);
+    self.set_edge_of_face(border, h1);
+    self.set_edge_of_vertex(a, h2);
+    self.set_edge_of_vertex(b, p);
+
+    self.destroy_halfedge(h0);
+    self.destroy_halfedge(t0);
+    self.destroy_face(face);
+    true
+  }
+
+  /// Restores the Delaunay property of the whole mesh by Lawson flips and returns the number of flips.
+  /// In contrast to [`Mesh::legalize`] any edge might be illegal, e.g., after moving vertices, but no face may be inverted.
+  pub fn make_delaunay(&mut self) -> usize {
+    let mut stack: Vec<usize> = Vec::new();
+    let mut flips = 0;
+    for edge in 0..self.halfedges.len() {
+      if !self.halfedges[edge].is_valid() || edge > self.twin(edge) {
+        continue;
+      }
+
+      stack.push(edge);
+      while let Some(halfedge) = stack.pop() {
+        if self.is_illegal(halfedge) {
+          self.flip(halfedge);
+          flips += 1;
+          let twin = self.twin(halfedge);
+          stack.extend([self.next(halfedge), self.prev(halfedge), self.next(twin), self.prev(twin)]);
+        }
+      }
+    }
+    flips
+  }
+
+  /// Fills concave corners of the boundary with new triangles until the boundary is the convex hull of its vertices
+  /// and returns the number of new faces. The new faces are not legalized, see [`Mesh::make_delaunay`].
+  pub fn convexify(&mut self) -> usize {
+    let mut count = 0;
+    loop {
+      let border: Vec<usize> = self.iter_face(self.boundary).collect();
+      if border.len() <= 3 {
+        break;
+      }
+      match self.find_ear(&border, &border, false) {
+        Some(halfedge) => {
+          self.connect(halfedge);
+          count += 1;
+        },
+        None => break,
+      }
+    }
+    count
+  }
+
+  /// Restores the Delaunay property around ```edge``` by recursively flipping illegal edges.
+  /// The point opposite of ```edge``` is assumed to be the most recently inserted one.
   pub fn legalize(&mut self, edge: usize) {
     if self.is_illegal(edge) {
       let twin = self.twin(edge);
-      let p = self.vertex(self.next(edge));
-  
       self.flip(edge);
-  
-      let vertex = self.vertex(edge);
-  
-      if vertex == p {
-        let e1 = self.prev(edge);
-        let e2 = self.next(self.twin(twin));
-        self.legalize(e1);
-        self.legalize(e2);
-      }
-      else {
-        let e1 = self.next(edge);
-        let e2 = self.prev(self.twin(edge));
-        self.legalize(e1);
-        self.legalize(e2);
-      }
+      let e1 = self.prev(edge);
+      let e2 = self.next(twin);
+      self.legalize(e1);
+      self.legalize(e2);
     }
   }
   
   pub fn is_illegal(&self, a: usize) -> bool {
+    if self.is_constrained(a) {
+      return false;
+    }
     let b: usize = self.twin(a);
   
     // if the pair of triangles doesn't satisfy the Delaunay condition
@@ -295,7 +1172,7 @@ impl Mesh {
     //
     let ar = self.prev(a);
   
-    if self.is_border(b) {
+    if !self.is_normal(self.face(a)) || !self.is_normal(self.face(b)) {
         return false;
     }
   
@@ -307,9 +1184,7 @@ impl Mesh {
     let pr = self.point_of_edge(ar);
     let pl = self.point_of_edge(bl);
     let p1 = self.point_of_edge(br);
-    // TODO: is the order right?
-    let in_circ = p0.in_circle(pr, p1, pl);
-    in_circ
+    p0.in_circle(pr, p1, pl)
   }
 
   pub fn boundary(&self) -> usize {
@@ -342,14 +1217,19 @@ impl Mesh {
     self.halfedges[halfedge].end
   }
 
+  /// Returns **true** if and only if ```halfedge``` is part of the boundary or of a hole.
   pub fn is_border(&self, halfedge: usize) -> bool {
     debug_assert!(halfedge != EMPTY);
     let face = self.face(halfedge);
-    let twin_face = self.face(self.twin(halfedge));
-    self.faces[face].face_type == FaceType::Boundary || self.faces[twin_face].face_type == FaceType::Hole
+    self.faces[face].face_type == FaceType::Boundary || self.faces[face].face_type == FaceType::Hole
   }
 
-  pub fn edge_of_vertex(&self, vertex: usize) -> usize {
+  pub fn is_normal(&self, face: usize) -> bool {
+    debug_assert!(face != EMPTY);
+    self.faces[face].face_type == FaceType::Normal
+  }
+
+  pub fn edge_of_vertex(&self, vertex: usize) -> usize {
     debug_assert!(vertex != EMPTY);
     debug_assert!(self.vertices.len() > vertex);
     self.vertices[vertex].halfedge
@@ -380,31 +1260,208 @@ impl Mesh {
     self.faces.len()
   } 
 
+  pub fn number_of_vertices(&self) -> usize {
+    self.vertices.len()
+  }
+
+  /// Removes all destroyed faces, halfedges and vertices and renumbers the remaining elements (keeping their relative order)
+  /// such that the id of each element is equal to its index. Returns the old-to-new index maps of each element type.
+  pub fn compact(&mut self) -> IndexMaps {
+    fn index_map<T>(elements: &[T], keep: impl Fn(&T) -> bool) -> Vec<usize> {
+      let mut count = 0;
+      elements.iter().map(|element| {
+        if keep(element) {
+          count += 1;
+          count - 1
+        } else {
+          EMPTY
+        }
+      }).collect()
+    }
+
+    let maps = IndexMaps {
+      faces: index_map(&self.faces, |face| face.face_type != FaceType::Destroyed),
+      halfedges: index_map(&self.halfedges, |halfedge| halfedge.is_valid()),
+      vertices: index_map(&self.vertices, |vertex| !vertex.destroyed),
+    };
+    let map = |m: &[usize], index: usize| if index == EMPTY { EMPTY } else { m[index] };
+
+    self.faces.retain(|face| face.face_type != FaceType::Destroyed);
+    for face in self.faces.iter_mut() {
+      face.id = maps.faces[face.id];
+      face.halfedge = map(&maps.halfedges, face.halfedge);
+    }
+
+    self.halfedges.retain(|halfedge| halfedge.is_valid());
+    for halfedge in self.halfedges.iter_mut() {
+      halfedge.id = maps.halfedges[halfedge.id];
+      halfedge.end = map(&maps.vertices, halfedge.end);
+      halfedge.next = map(&maps.halfedges, halfedge.next);
+      halfedge.prev = map(&maps.halfedges, halfedge.prev);
+      halfedge.twin = map(&maps.halfedges, halfedge.twin);
+      halfedge.face = map(&maps.faces, halfedge.face);
+    }
+
+    self.vertices.retain(|vertex| !vertex.destroyed);
+    for vertex in self.vertices.iter_mut() {
+      vertex.id = maps.vertices[vertex.id];
+      vertex.halfedge = map(&maps.halfedges, vertex.halfedge);
+    }
+
+    self.boundary = map(&maps.faces, self.boundary);
+    self.holes = self.holes.iter().map(|&hole| map(&maps.faces, hole)).filter(|&hole| hole != EMPTY).collect();
+    maps
+  }
+
   pub fn validate(&self) -> bool {
     let valid_faces = self.faces.iter().enumerate().all(|(index, face)| {face.id == index});
     let valid_halfedges = self.halfedges.iter().enumerate().all(|(index, halfedge)| {halfedge.id == index});
     let valid_vertices = self.vertices.iter().enumerate().all(|(index, vertex)| {vertex.id == index});
-    return valid_faces && valid_halfedges && valid_vertices
+    valid_faces && valid_halfedges && valid_vertices
+  }
+
+  /// Verifies all topological invariants of the mesh and returns every violation found.
+  /// Destroyed elements are ignored and isolated vertices are allowed. If the connectivity itself is broken
+  /// (dangling references, twins, next/prev or face cycles) the geometric checks are skipped.
+  pub fn check(&self) -> Result<(), Vec<MeshError>> {
+    let errors = self.find_errors();
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+  }
+
+  /// Same as [`Mesh::check`] but additionally verifies that all edges are locally Delaunay.
+  pub fn check_delaunay(&self) -> Result<(), Vec<MeshError>> {
+    let mut errors = self.find_errors();
+    if errors.is_empty() {
+      errors.extend((0..self.halfedges.len())
+        .filter(|&h| self.halfedges[h].is_valid() && h < self.twin(h) && self.is_illegal(h))
+        .map(MeshError::NotDelaunay));
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
   }
 
   pub fn find_visible_edge(&self, p: &Point) -> Option<usize> {
-    for halfedge in self.iter_face(self.boundary()) {
+    self.find_visible_border_edge(self.boundary(), p)
+  }
+
+  /// Returns a halfedge of the boundary or hole ```face``` which is visible from ```p```, i.e. ```p``` lies strictly to its left.
+  pub fn find_visible_border_edge(&self, face: usize, p: &Point) -> Option<usize> {
+    for halfedge in self.iter_face(face) {
       let u2 = self.point_of_edge(halfedge);
       let u1 = self.point_of_edge(self.twin(halfedge));
-      if p.orient(u1, u2) <= 0. {
+      if u1.orient(u2, p) > 0. {
         return Some(halfedge)
       }
     }
     None
   }
 
-  fn empty() -> Self {
+  /// Locates the point ```p``` by a jump-and-walk: the walk starts at the face of the closest of a few sampled vertices.
+  /// If the mesh has no faces, ```Location::Outside(EMPTY)``` is returned.
+  pub fn locate(&self, p: &Point) -> Location {
+    match self.sample_face(p) {
+      Some(face) => self.locate_from(p, face),
+      None => Location::Outside(EMPTY),
+    }
+  }
+
+  /// Locates the point ```p``` by a visibility walk starting at the (normal) ```face``` which should be close to ```p```.
+  pub fn locate_from(&self, p: &Point, face: usize) -> Location {
+    debug_assert!(self.is_normal(face));
+    let mut face = face;
+    let mut from = EMPTY;
+    // a cheap pseudo random generator to choose the first edge tested, which prevents cycles in non-Delaunay meshes
+    let mut seed: u32 = 0x9E37_79B9;
+
+    'walk: loop {
+      seed ^= seed << 13;
+      seed ^= seed >> 17;
+      seed ^= seed << 5;
+
+      let start = self.edge_of_face(face);
+      let mut halfedge = start;
+      for _ in 0..(seed % 3) {
+        halfedge = self.next(halfedge);
+      }
+
+      let mut on_line = [EMPTY; 2];
+      for _ in 0..3 {
+        let twin = self.twin(halfedge);
+        if halfedge != from {
+          let u1 = self.point_of_edge(twin);
+          let u2 = self.point_of_edge(halfedge);
+
+          if u2.nearly_equals(p) {
+            return Location::OnVertex(self.vertex(halfedge));
+          }
+
+          let orientation = u1.orient(u2, p);
+          if orientation < 0.0 {
+            if !self.is_normal(self.face(twin)) {
+              return Location::Outside(twin);
+            }
+            from = twin;
+            face = self.face(twin);
+            continue 'walk;
+          } else if orientation == 0.0 {
+            if on_line[0] == EMPTY {
+              on_line[0] = halfedge;
+            } else {
+              on_line[1] = halfedge;
+            }
+          }
+        } else if self.point_of_edge(halfedge).nearly_equals(p) {
+          return Location::OnVertex(self.vertex(halfedge));
+        }
+        halfedge = self.next(halfedge);
+      }
+
+      // p is not right of any edge, therefore, it is inside the face or on its border
+      return match on_line {
+        [EMPTY, _] => Location::InFace(face),
+        [e, EMPTY] => Location::OnEdge(e),
+        [e1, e2] => {
+          if self.vertex(e1) == self.vertex(self.prev(e2)) {
+            Location::OnVertex(self.vertex(e1))
+          } else {
+            Location::OnVertex(self.vertex(e2))
+          }
+        }
+      };
+    }
+  }
+
+  /// Returns a normal face close to ```p``` by sampling about n^(1/3) vertices where n is the number of vertices.
+  fn sample_face(&self, p: &Point) -> Option<usize> {
+    let nsamples = (self.vertices.len() as f64).cbrt().ceil() as usize;
+    let stride = (self.vertices.len() / nsamples.max(1)).max(1);
+
+    let closest = self.vertices.iter()
+      .step_by(stride)
+      .filter(|vertex| vertex.halfedge != EMPTY)
+      .min_by(|v1, v2| v1.point.distance_sq(p).partial_cmp(&v2.point.distance_sq(p)).unwrap());
+
+    match closest {
+      Some(vertex) => {
+        let halfedge = vertex.halfedge;
+        if self.is_normal(self.face(halfedge)) {
+          Some(self.face(halfedge))
+        } else if self.is_normal(self.face(self.twin(halfedge))) {
+          Some(self.face(self.twin(halfedge)))
+        } else {
+          self.some_face()
+        }
+      },
+      None => self.some_face()
+    }
+  }
+
+  pub(crate) fn empty() -> Self {
     let mut mesh = Mesh {faces: Vec::new(), holes: Vec::new(), halfedges: Vec::new(), vertices: Vec::new(), boundary: EMPTY};
     mesh.create_face(FaceType::Boundary);
     mesh
   }
 
-  fn create_vertex(&mut self, u: Point) -> usize {
+  pub(crate) fn create_vertex(&mut self, u: Point) -> usize {
     let id = self.vertices.len();
     let vertex = Vertex::empty(id, u);
     self.vertices.push(vertex);
@@ -421,6 +1478,28 @@ impl Mesh {
     id
   }
 
+  /// Connects the three unconnected vertices ```v1```, ```v2``` and ```v3``` (in ccw order) to a triangle surrounded by the boundary.
+  pub(crate) fn create_triangle(&mut self, v1: usize, v2: usize, v3: usize) -> usize {
+    debug_assert!(self.point_of_vertex(v1).ccw(self.point_of_vertex(v2), self.point_of_vertex(v3)));
+    let inner_face = self.create_face(FaceType::Normal);
+
+    let halfedge1 = self.create_halfedge(v1, Some(inner_face));
+    let halfedge2 = self.create_halfedge(v2, Some(inner_face));
+    let halfedge3 = self.create_halfedge(v3, Some(inner_face));
+    self.set_edge_of_vertex(v1, halfedge1);
+    self.set_edge_of_vertex(v2, halfedge2);
+    self.set_edge_of_vertex(v3, halfedge3);
+    self.set_edge_of_face(inner_face, halfedge1);
+
+    let twin1 = self.create_halfedge(v3, Some(self.boundary));
+    let twin2 = self.create_halfedge(v1, Some(self.boundary));
+    let twin3 = self.create_halfedge(v2, Some(self.boundary));
+    self.set_edge_of_face(self.boundary, twin1);
+    
+    self.set_cycle_and_twins(halfedge1, halfedge2, halfedge3, twin1, twin2, twin3);
+    inner_face
+  }
+
   fn set_edge_of_vertex(&mut self, vertex: usize, halfedge: usize) {
     debug_assert!(self.vertices.len() > vertex);
     self.vertices[vertex].halfedge = halfedge;
@@ -500,6 +1579,464 @@ impl Mesh {
     self.halfedges[halfedge].face = face;
   }
 
+  fn set_constrained(&mut self, halfedge: usize, constrained: bool) {
+    let twin = self.twin(halfedge);
+    self.halfedges[halfedge].constrained = constrained;
+    self.halfedges[twin].constrained = constrained;
+  }
+
+  /// Makes sure that there is an edge from ```a``` along the segment from ```a``` to ```b``` by flipping all edges crossing the segment.
+  /// Returns the halfedge starting at ```a``` and ending at the first vertex on the segment, which is either ```b``` or a vertex in between.
+  fn recover_segment(&mut self, a: usize, b: usize) -> Result<usize, ConstraintError> {
+    let pa = self.point_of_vertex(a).clone();
+    let pb = self.point_of_vertex(b).clone();
+
+    // the segment starts along an existing edge
+    let along = self.iter_outgoing_edges(a).find(|&h| {
+      let pc = self.point_of_edge(h);
+      self.vertex(h) == b || (pa.orient(&pb, pc) == 0.0 && pc.distance_sq(&pb) < pa.distance_sq(&pb))
+    });
+    if let Some(halfedge) = along {
+      return Ok(halfedge);
+    }
+
+    // the triangle (a, c, d) whose angle at a contains the segment
+    let start = self.iter_incoming_edges(a).find(|&h| {
+      self.is_normal(self.face(h)) &&
+      pa.orient(self.point_of_edge(self.next(h)), &pb) > 0.0 &&
+      pa.orient(self.point_of_edge(self.prev(h)), &pb) < 0.0
+    }).ok_or(ConstraintError::Outside)?;
+
+    // walk along the segment and gather all crossed edges, each pointing from the right to the left of the segment
+    let mut crossing = Vec::new();
+    let mut e = self.prev(start);
+    let end = loop {
+      let t = self.twin(e);
+      if self.is_constrained(e) {
+        return Err(ConstraintError::Crossing);
+      }
+      if !self.is_normal(self.face(t)) {
+        return Err(ConstraintError::Outside);
+      }
+      crossing.push(e);
+
+      let o = self.vertex(self.next(t));
+      let side = pa.orient(&pb, self.point_of_vertex(o));
+      if side == 0.0 {
+        break o;
+      }
+      e = if side > 0.0 { self.next(t) } else { self.prev(t) };
+    };
+
+    // flip the crossed edges until none of them crosses the segment (Sloan, 1993)
+    let pend = self.point_of_vertex(end).clone();
+    let mut queue: VecDeque<usize> = crossing.into();
+    let mut new_edges = Vec::new();
+    while let Some(e) = queue.pop_front() {
+      if !self.is_convex(e) {
+        queue.push_back(e);
+        continue;
+      }
+      self.flip(e);
+      let p = self.point_of_edge(self.twin(e));
+      let q = self.point_of_edge(e);
+      let crosses = pa.orient(&pend, p) * pa.orient(&pend, q) < 0.0 && p.orient(q, &pa) * p.orient(q, &pend) < 0.0;
+      if crosses {
+        queue.push_back(e);
+      } else {
+        new_edges.push(e);
+      }
+    }
+
+    let halfedge = self.iter_outgoing_edges(a).find(|&h| self.vertex(h) == end).unwrap();
+    self.set_constrained(halfedge, true);
+
+    // restore the Delaunay property of the new edges
+    let mut flipped = true;
+    while flipped {
+      flipped = false;
+      for &e in new_edges.iter() {
+        if self.is_illegal(e) {
+          self.flip(e);
+          flipped = true;
+        }
+      }
+    }
+    Ok(halfedge)
+  }
+
+  /// Merges all normal faces reachable from ```face``` without crossing a constrained edge into a new hole face.
+  fn carve_region(&mut self, face: usize) -> Result<usize, HoleError> {
+    let mut in_region = vec![false; self.faces.len()];
+    let mut region = vec![face];
+    in_region[face] = true;
+    let mut i = 0;
+    while i < region.len() {
+      for halfedge in self.iter_face(region[i]) {
+        let neighbour = self.face(self.twin(halfedge));
+        if self.is_constrained(halfedge) {
+          continue;
+        }
+        if !self.is_normal(neighbour) {
+          return Err(HoleError::Border);
+        }
+        if !in_region[neighbour] {
+          in_region[neighbour] = true;
+          region.push(neighbour);
+        }
+      }
+      i += 1;
+    }
+
+    let halfedges: Vec<usize> = region.iter().flat_map(|&face| self.iter_face(face)).collect();
+    let border: Vec<usize> = halfedges.iter().copied().filter(|&h| !in_region[self.face(self.twin(h))]).collect();
+    if border.iter().any(|&h| !self.is_normal(self.face(self.twin(h))) || self.is_boundary_vertex(self.vertex(h))) {
+      return Err(HoleError::Border);
+    }
+
+    // the successor of a border halfedge is the next border halfedge around its end vertex
+    let nexts: Vec<usize> = border.iter().map(|&h| {
+      let mut n = self.next(h);
+      while in_region[self.face(self.twin(n))] {
+        n = self.next(self.twin(n));
+      }
+      n
+    }).collect();
+
+    // the border has to be a single cycle without touching itself
+    let mut position = vec![EMPTY; self.halfedges.len()];
+    for (i, &h) in border.iter().enumerate() {
+      position[h] = i;
+    }
+    let mut vertices: Vec<usize> = border.iter().map(|&h| self.vertex(h)).collect();
+    vertices.sort_unstable();
+    vertices.dedup();
+    let mut length = 1;
+    let mut h = nexts[0];
+    while h != border[0] && length <= border.len() {
+      h = nexts[position[h]];
+      length += 1;
+    }
+    if vertices.len() != border.len() || length != border.len() {
+      return Err(HoleError::NotSimple);
+    }
+
+    let hole = self.create_face(FaceType::Hole);
+    for (&h, &n) in border.iter().zip(nexts.iter()) {
+      self.set_face(h, hole);
+      self.set_next(h, n);
+      self.set_prev(n, h);
+      self.set_edge_of_vertex(self.vertex(h), h);
+    }
+    self.set_edge_of_face(hole, border[0]);
+
+    for &h in halfedges.iter().filter(|&&h| position[h] == EMPTY) {
+      let v = self.vertex(h);
+      if vertices.binary_search(&v).is_err() && !self.vertices[v].destroyed {
+        self.destroy_vertex(v);
+      }
+      self.destroy_halfedge(h);
+    }
+    for face in region {
+      self.destroy_face(face);
+    }
+    Ok(hole)
+  }
+
+  /// Returns **true** if and only if the two triangles sharing the edge of ```halfedge``` form a strictly convex quadrilateral.
+  fn is_convex(&self, halfedge: usize) -> bool {
+    let twin = self.twin(halfedge);
+    let p0 = self.point_of_edge(halfedge);
+    let p1 = self.point_of_edge(twin);
+    let pl = self.point_of_edge(self.next(halfedge));
+    let pr = self.point_of_edge(self.next(twin));
+    pl.orient(pr, p0) * pl.orient(pr, p1) < 0.0
+  }
+
+  /// Returns all halfedges ending at ```vertex```.
+  fn incoming_edges(&self, vertex: usize) -> Vec<usize> {
+    self.iter_incoming_edges(vertex).collect()
+  }
+
+  fn is_border_edge(&self, halfedge: usize) -> bool {
+    !self.is_normal(self.face(halfedge)) || !self.is_normal(self.face(self.twin(halfedge)))
+  }
+
+  /// Triangulates the star-shaped polygon ```face``` by cutting off ears whose circumcircle contains no other vertex of the polygon.
+  fn triangulate_polygon(&mut self, face: usize) {
+    loop {
+      let halfedges: Vec<usize> = self.iter_face(face).collect();
+      if halfedges.len() <= 3 {
+        break;
+      }
+
+      let ear = self.find_ear(&halfedges, &halfedges, true).or_else(|| self.find_ear(&halfedges, &halfedges, false));
+      match ear {
+        Some(halfedge) => {
+          let prev = self.prev(halfedge);
+          let next = self.next(halfedge);
+          self.insert_diagonal(prev, next);
+        },
+        None => break,
+      }
+    }
+  }
+
+  /// Closes the concave corners of the border chain between ```before``` and ```after``` (exclusive) by new triangles
+  /// which do not cover ```removed```, i.e. the position of a removed vertex.
+  fn fill_border(&mut self, before: usize, after: usize, removed: &Point) {
+    loop {
+      let mut chain = Vec::new();
+      let mut halfedge = self.next(before);
+      while halfedge != after && halfedge != before {
+        chain.push(halfedge);
+        halfedge = self.next(halfedge);
+      }
+
+      if chain.len() < 2 {
+        break;
+      }
+
+      // the mesh lies to the right of the border (the boundary is clockwise, holes are ccw), i.e. an ear lies to the left of the chain.
+      // Other vertices of the border loop may lie close to the chain, e.g. if v was a corner of a hole, hence, the ear must not contain any of them.
+      let border: Vec<usize> = self.iter_face(self.face(before)).collect();
+      let candidates: Vec<usize> = chain[..chain.len()-1].iter().copied().filter(|&h| {
+        let (prev, next) = (self.prev(h), self.next(h));
+        let a = self.point_of_edge(prev);
+        let b = self.point_of_edge(h);
+        let c = self.point_of_edge(next);
+        // a (numerically) flat ear would become a degenerate sliver, instead the almost straight corner remains part of the border
+        a.orient(b, c) > FLAT_EAR * a.distance_sq(c) &&
+        !(a.ccw(b, removed) && b.ccw(c, removed) && c.ccw(a, removed)) && border.iter()
+          .filter(|&&e| e != prev && e != h && e != next)
+          .map(|&e| self.point_of_edge(e))
+          .all(|q| !(a.orient(b, q) >= 0.0 && b.orient(c, q) >= 0.0 && c.orient(a, q) >= 0.0))
+      }).collect();
+
+      let mut vertices = vec![self.prev(chain[0])];
+      vertices.extend(chain.iter());
+      let ear = self.find_ear(&candidates, &vertices, true).or_else(|| self.find_ear(&candidates, &vertices, false));
+      match ear {
+        Some(halfedge) => {
+          let next = self.next(halfedge);
+          self.connect(halfedge);
+          self.legalize(halfedge);
+          self.legalize(next);
+        },
+        None => break,
+      }
+    }
+  }
+
+  /// Returns a halfedge of ```candidates``` whose end forms a ccw ear together with its neighbours such that none of the vertices (ends) 
+  /// of ```halfedges``` lie within the circumcircle of the ear (if ```delaunay``` is **true**) or within the ear itself.
+  fn find_ear(&self, candidates: &[usize], halfedges: &[usize], delaunay: bool) -> Option<usize> {
+    candidates.iter().copied().find(|&h| {
+      let prev = self.prev(h);
+      let next = self.next(h);
+      let a = self.point_of_edge(prev);
+      let b = self.point_of_edge(h);
+      let c = self.point_of_edge(next);
+
+      if !a.ccw(b, c) {
+        return false;
+      }
+
+      halfedges.iter().copied()
+        .filter(|&e| e != prev && e != h && e != next)
+        .map(|e| self.point_of_edge(e))
+        .all(|q| {
+          if delaunay {
+            !q.in_circle(a, b, c)
+          } else {
+            !(a.orient(b, q) >= 0.0 && b.orient(c, q) >= 0.0 && c.orient(a, q) >= 0.0)
+          }
+        })
+    })
+  }
+
+  fn find_errors(&self) -> Vec<MeshError> {
+    let mut errors = Vec::new();
+    let live_face = |face: usize| face < self.faces.len() && self.faces[face].face_type != FaceType::Destroyed;
+    let faces: Vec<usize> = (0..self.faces.len()).filter(|&f| live_face(f) && self.faces[f].halfedge != EMPTY).collect();
+    let live_halfedge = |halfedge: usize| halfedge < self.halfedges.len() && self.halfedges[halfedge].is_valid();
+    let live_vertex = |vertex: usize| vertex < self.vertices.len() && !self.vertices[vertex].destroyed;
+    let halfedges: Vec<usize> = (0..self.halfedges.len()).filter(|&h| live_halfedge(h)).collect();
+
+    // ids and references
+    for (index, face) in self.faces.iter().enumerate() {
+      if face.id != index {
+        errors.push(MeshError::FaceId(index));
+      }
+      // the boundary of a mesh without any face is empty
+      let empty_boundary = face.face_type == FaceType::Boundary && face.halfedge == EMPTY;
+      if face.face_type != FaceType::Destroyed && !empty_boundary && !live_halfedge(face.halfedge) {
+        errors.push(MeshError::DanglingFace(index));
+      }
+    }
+    for (index, halfedge) in self.halfedges.iter().enumerate() {
+      if halfedge.id != index {
+        errors.push(MeshError::HalfedgeId(index));
+      }
+      let destroyed = halfedge.end == EMPTY && halfedge.next == EMPTY && halfedge.prev == EMPTY && halfedge.twin == EMPTY && halfedge.face == EMPTY;
+      let linked = live_vertex(halfedge.end) && live_halfedge(halfedge.next) && live_halfedge(halfedge.prev)
+        && live_halfedge(halfedge.twin) && live_face(halfedge.face);
+      if !destroyed && !linked {
+        errors.push(MeshError::DanglingHalfedge(index));
+      }
+    }
+    for (index, vertex) in self.vertices.iter().enumerate() {
+      if vertex.id != index {
+        errors.push(MeshError::VertexId(index));
+      }
+      if !vertex.destroyed && vertex.halfedge != EMPTY && !live_halfedge(vertex.halfedge) {
+        errors.push(MeshError::DanglingVertex(index));
+      }
+    }
+    if !errors.is_empty() {
+      return errors;
+    }
+
+    // connectivity
+    for &h in halfedges.iter() {
+      let twin = self.twin(h);
+      if twin == h || self.twin(twin) != h || self.vertex(twin) != self.vertex(self.prev(h)) {
+        errors.push(MeshError::Twin(h));
+      }
+      if self.prev(self.next(h)) != h || self.next(self.prev(h)) != h {
+        errors.push(MeshError::NextPrev(h));
+      }
+    }
+    let mut on_cycle = vec![false; self.halfedges.len()];
+    for &face in faces.iter() {
+      let start = self.edge_of_face(face);
+      let mut h = start;
+      for _ in 0..halfedges.len() {
+        if self.face(h) != face || on_cycle[h] {
+          break;
+        }
+        on_cycle[h] = true;
+        h = self.next(h);
+      }
+      if h != start {
+        errors.push(MeshError::FaceCycle(h));
+      }
+    }
+    errors.extend(halfedges.iter().filter(|&&h| !on_cycle[h]).map(|&h| MeshError::FaceCycle(h)));
+    for vertex in self.iter_vertices() {
+      let halfedge = self.edge_of_vertex(vertex);
+      if halfedge != EMPTY && self.vertex(halfedge) != vertex {
+        errors.push(MeshError::VertexHalfedge(vertex));
+      }
+    }
+    if !errors.is_empty() {
+      return errors;
+    }
+
+    // orientation
+    for &face in faces.iter() {
+      let points: Vec<&Point> = self.iter_face(face).map(|h| self.point_of_edge(h)).collect();
+      let area: f64 = (0..points.len()).map(|i| {
+        let (p, q) = (points[i], points[(i + 1) % points.len()]);
+        p.x * q.y - q.x * p.y
+      }).sum();
+      let ccw = self.faces[face].face_type != FaceType::Boundary;
+      if (ccw && area <= 0.0) || (!ccw && area >= 0.0) {
+        errors.push(MeshError::Orientation(face));
+      }
+    }
+
+    // manifoldness: all incoming halfedges form a single cycle which passes the border at most once
+    let mut incoming = vec![0; self.vertices.len()];
+    let mut border = vec![0; self.vertices.len()];
+    for &h in halfedges.iter() {
+      incoming[self.vertex(h)] += 1;
+      if !self.is_normal(self.face(h)) {
+        border[self.vertex(h)] += 1;
+      }
+    }
+    for vertex in self.iter_vertices() {
+      let start = self.edge_of_vertex(vertex);
+      if start == EMPTY {
+        continue;
+      }
+      let mut count = 0;
+      let mut h = start;
+      loop {
+        count += 1;
+        h = self.twin(self.next(h));
+        if h == start || count > incoming[vertex] {
+          break;
+        }
+      }
+      if count != incoming[vertex] || border[vertex] > 1 {
+        errors.push(MeshError::NonManifoldVertex(vertex));
+      }
+    }
+
+    // Euler characteristic of a planar mesh with one outer border and some holes
+    if !halfedges.is_empty() {
+      let v = incoming.iter().filter(|&&count| count > 0).count() as i64;
+      let e = halfedges.len() as i64 / 2;
+      let f = self.iter_faces().count() as i64;
+      let holes = self.faces.iter().filter(|face| face.face_type == FaceType::Hole).count() as i64;
+      if v - e + f != 1 - holes {
+        errors.push(MeshError::EulerCharacteristic { expected: 1 - holes, actual: v - e + f });
+      }
+    }
+    errors
+  }
+
+  fn destroy_face(&mut self, face: usize) {
+    self.faces[face].face_type = FaceType::Destroyed;
+    self.faces[face].halfedge = EMPTY;
+  }
+
+  fn destroy_halfedge(&mut self, halfedge: usize) {
+    let id = self.halfedges[halfedge].id;
+    self.halfedges[halfedge] = Halfedge::empty(id);
+  }
+
+  fn destroy_vertex(&mut self, vertex: usize) {
+    self.vertices[vertex].halfedge = EMPTY;
+    self.vertices[vertex].destroyed = true;
+  }
+
+  /// Cuts the face of ```h1``` and ```h2``` into two by inserting a new edge between the end of ```h1``` and the end of ```h2```.
+  /// The face of ```h1``` keeps ```h1```, the new face contains ```h2```. Returns the new halfedge which is part of the face of ```h1```.
+  fn insert_diagonal(&mut self, h1: usize, h2: usize) -> usize {
+    let face = self.face(h1);
+    debug_assert!(face == self.face(h2));
+    debug_assert!(h1 != h2 && self.next(h1) != h2 && self.next(h2) != h1);
+
+    let n1 = self.next(h1);
+    let n2 = self.next(h2);
+    let new_face = self.create_face(FaceType::Normal);
+
+    let d = self.create_halfedge(self.vertex(h2), Some(face));
+    let dt = self.create_halfedge(self.vertex(h1), Some(new_face));
+    self.set_twins(d, dt);
+
+    self.set_next(h1, d);
+    self.set_prev(d, h1);
+    self.set_next(d, n2);
+    self.set_prev(n2, d);
+
+    self.set_next(h2, dt);
+    self.set_prev(dt, h2);
+    self.set_next(dt, n1);
+    self.set_prev(n1, dt);
+
+    self.set_edge_of_face(face, h1);
+    self.set_edge_of_face(new_face, h2);
+    let mut e = n1;
+    while e != dt {
+      self.set_face(e, new_face);
+      e = self.next(e);
+    }
+    d
+  }
+
   fn flip(&mut self, halfedge: usize) {
 
     // if the pair of triangles doesn't satisfy the Delaunay condition
@@ -557,23 +2094,18 @@ impl Mesh {
       self.set_edge_of_vertex(vb0, a2);
     }
   
-    self.set_edge_of_vertex(a0, va1);
+    self.set_vertex(a0, va1);
     self.set_vertex(b0, vb1);
   
-    self.set_next(a0, a2);
-    self.set_next(a2, b1);
-    self.set_next(b1, a0);
-  
-    self.set_next(b0, b2);
-    self.set_next(b2, a1);
-    self.set_next(a1, b0);
+    self.set_cycle(a0, a2, b1);
+    self.set_cycle(b0, b2, a1);
   
     self.set_face(a1, fb);
     self.set_face(b1, fa);
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum FaceType {
   Normal, 
   Hole, 
@@ -581,7 +2113,7 @@ enum FaceType {
   Destroyed,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Halfedge {
   id: usize,
   end: usize,
@@ -589,12 +2121,13 @@ struct Halfedge {
   prev: usize,
   twin: usize,
   face: usize,
+  constrained: bool,
 }
 
 impl Halfedge {
 
   fn empty(id: usize) -> Self {
-    Self {id, end: EMPTY, next: EMPTY, prev: EMPTY, twin: EMPTY, face: EMPTY}
+    Self {id, end: EMPTY, next: EMPTY, prev: EMPTY, twin: EMPTY, face: EMPTY, constrained: false}
   }
 
   fn is_valid(&self) -> bool {
@@ -602,7 +2135,7 @@ impl Halfedge {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Face {
   id: usize,
   halfedge: usize,
@@ -611,36 +2144,31 @@ struct Face {
 
 impl Face {
 
-  fn empty(id: usize) -> Self {
-    Self {id, halfedge: EMPTY, face_type: FaceType::Normal}
-  }
-
   fn new(id: usize, edge: usize, face_type: FaceType) -> Self {
-    Self {id, halfedge: edge, face_type: face_type}
+    Self {id, halfedge: edge, face_type}
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Vertex {
   id: usize,
   halfedge: usize,
   point: Point,
+  destroyed: bool,
 }
 
 impl Vertex {
 
   fn empty(id: usize, point: Point) -> Self {
-    Self {id, halfedge: EMPTY, point}
-  }
-
-  fn new(id: usize, halfedge: usize, point: Point) -> Self {
-    Self {id, halfedge, point}
+    Self {id, halfedge: EMPTY, point, destroyed: false}
   }
 }
 
+#[cfg(test)]
 mod testing {
   use super::*;
   use crate::geometry::equiliteral_triangle;
+  use crate::testing::seeded_points;
 
   #[test]
   fn simple_face_iteration() {
@@ -670,7 +2198,7 @@ mod testing {
     assert!(mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.point_of_edge(halfedge)).any(|p| p.x == p1.x && p.y == p1.y));
     assert!(mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.point_of_edge(halfedge)).any(|p| p.x == p2.x && p.y == p2.y));
     assert!(mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.point_of_edge(halfedge)).any(|p| p.x == p3.x && p.y == p3.y));
-    assert!(mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.point_of_edge(halfedge)).count() == 3);
+    assert!(mesh.iter_face(mesh.boundary()).count() == 3);
   }
 
   #[test]
@@ -693,4 +2221,750 @@ mod testing {
     let mesh = Mesh::triangle(p1, p2, p3);
     assert!(mesh.iter_vertices().count() == 3);
   }
-}
\ No newline at end of file
+
+  fn assert_consistent(mesh: &Mesh) {
+    assert_valid(mesh);
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+  }
+
+  fn assert_valid(mesh: &Mesh) {
+    assert_eq!(mesh.check(), Ok(()));
+    for face in mesh.iter_faces() {
+      assert_eq!(mesh.iter_face(face).count(), 3);
+    }
+  }
+
+  #[test]
+  fn test_split_face() {
+    let (p1, p2, p3) = equiliteral_triangle(1.0);
+    let mut mesh = Mesh::triangle(p1, p2, p3);
+    let face = mesh.some_face().unwrap();
+    let v = mesh.split_face(face, Point {x: 0.5, y: 0.3});
+    assert_eq!(mesh.iter_faces().count(), 3);
+    assert_eq!(mesh.iter_edges().count(), 12);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 3);
+    assert!(mesh.point_of_vertex(v).nearly_equals(&Point {x: 0.5, y: 0.3}));
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_split_face_legalizes() {
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 10.0, y: 0.0},
+      Point {x: 10.0, y: 10.0},
+      Point {x: 0.0, y: 10.0},
+    ];
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let p = Point {x: 9.0, y: 5.0};
+    let face = mesh.iter_faces().find(|&face| {
+      let e = mesh.iter_face(face).collect::<Vec<usize>>();
+      (0..3).all(|i| mesh.point_of_edge(e[i]).ccw(mesh.point_of_edge(e[(i + 1) % 3]), &p))
+    }).unwrap();
+    mesh.split_face(face, p);
+    assert_eq!(mesh.iter_faces().count(), 4);
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_split_border_edge() {
+    let (p1, p2, p3) = equiliteral_triangle(1.0);
+    let mut mesh = Mesh::triangle(p1, p2, p3);
+    let halfedge = mesh.edge_of_face(mesh.boundary());
+    let p = mesh.point_of_edge(halfedge).center(mesh.point_of_edge(mesh.twin(halfedge)));
+    mesh.split_edge(halfedge, p);
+    assert_eq!(mesh.iter_faces().count(), 2);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 4);
+    assert_consistent(&mesh);
+
+    let halfedge = mesh.twin(mesh.edge_of_face(mesh.boundary()));
+    let p = mesh.point_of_edge(halfedge).center(mesh.point_of_edge(mesh.twin(halfedge)));
+    mesh.split_edge(halfedge, p);
+    assert_eq!(mesh.iter_faces().count(), 3);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 5);
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_split_inner_edge() {
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 1.0, y: 0.0},
+      Point {x: 1.0, y: 1.0},
+      Point {x: 0.0, y: 1.0},
+    ];
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let halfedge = mesh.iter_edges().find(|&halfedge| !mesh.is_border(halfedge) && !mesh.is_border(mesh.twin(halfedge))).unwrap();
+    mesh.split_edge(halfedge, Point {x: 0.5, y: 0.5});
+    assert_eq!(mesh.iter_faces().count(), 4);
+    assert_eq!(mesh.iter_edges().count(), 16);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 4);
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_locate() {
+    let points = random_points(200);
+    let mesh = crate::triangulator::triangulate(&points);
+
+    for (v, p) in points.iter().enumerate() {
+      assert_eq!(mesh.locate(p), Location::OnVertex(v));
+    }
+
+    for face in mesh.iter_faces() {
+      let e: Vec<usize> = mesh.iter_face(face).collect();
+      let p1 = mesh.point_of_edge(e[0]);
+      let p2 = mesh.point_of_edge(e[1]);
+      let p3 = mesh.point_of_edge(e[2]);
+      let centroid = Point {x: (p1.x + p2.x + p3.x) / 3.0, y: (p1.y + p2.y + p3.y) / 3.0};
+      assert_eq!(mesh.locate(&centroid), Location::InFace(face));
+      assert_eq!(mesh.locate_from(&centroid, mesh.some_face().unwrap()), Location::InFace(face));
+    }
+
+    match mesh.locate(&Point {x: 100.0, y: 5.0}) {
+      Location::Outside(halfedge) => {
+        assert_eq!(mesh.face(halfedge), mesh.boundary());
+        let u1 = mesh.point_of_edge(mesh.twin(halfedge));
+        let u2 = mesh.point_of_edge(halfedge);
+        assert!(u1.ccw(u2, &Point {x: 100.0, y: 5.0}));
+      },
+      location => panic!("unexpected location {:?}", location),
+    }
+  }
+
+  #[test]
+  fn test_locate_on_edge() {
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 2.0, y: 0.0},
+      Point {x: 2.0, y: 2.0},
+      Point {x: 0.0, y: 2.0},
+      Point {x: 1.0, y: 1.5},
+    ];
+    let mesh = crate::triangulator::triangulate(&points);
+    let p = Point {x: 1.0, y: 0.0};
+    match mesh.locate(&p) {
+      Location::OnEdge(halfedge) => {
+        assert!(mesh.is_normal(mesh.face(halfedge)));
+        assert_eq!(mesh.point_of_edge(mesh.twin(halfedge)).orient(mesh.point_of_edge(halfedge), &p), 0.0);
+      },
+      location => panic!("unexpected location {:?}", location),
+    }
+    assert_eq!(Mesh::empty().locate(&p), Location::Outside(EMPTY));
+  }
+
+  fn random_points(n: usize) -> Vec<Point> {
+    seeded_points(n, 42, 10.0)
+  }
+
+  fn assert_convex_boundary(mesh: &Mesh) {
+    let hull: Vec<&Point> = mesh.iter_face(mesh.boundary()).map(|halfedge| mesh.point_of_edge(halfedge)).collect();
+    for i in 0..hull.len() {
+      assert!(hull[i].orient(hull[(i + 1) % hull.len()], hull[(i + 2) % hull.len()]) <= 0.0);
+    }
+  }
+
+  #[test]
+  fn test_remove_inner_vertex() {
+    let points = random_points(100);
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+    let v = mesh.iter_vertices().find(|&v| mesh.iter_face(mesh.boundary()).all(|halfedge| mesh.vertex(halfedge) != v)).unwrap();
+
+    mesh.remove_vertex(v);
+    assert_eq!(mesh.iter_vertices().count(), 99);
+    assert!(mesh.iter_vertices().all(|u| u != v));
+    assert_eq!(mesh.iter_faces().count(), 2 * 99 - nhull - 2);
+    assert!(mesh.iter_edges().all(|halfedge| mesh.vertex(halfedge) != v));
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_remove_border_vertex() {
+    let points = random_points(100);
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let v = mesh.vertex(mesh.edge_of_face(mesh.boundary()));
+
+    mesh.remove_vertex(v);
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+    assert_eq!(mesh.iter_faces().count(), 2 * 99 - nhull - 2);
+    assert!(mesh.iter_edges().all(|halfedge| mesh.vertex(halfedge) != v));
+    assert_convex_boundary(&mesh);
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_remove_many_vertices() {
+    let points = random_points(200);
+    let mut mesh = crate::triangulator::triangulate(&points);
+    for v in (0..200).step_by(3) {
+      mesh.remove_vertex(v);
+      assert_consistent(&mesh);
+    }
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+    let n = mesh.iter_vertices().count();
+    assert_eq!(n, 200 - 67);
+    assert_eq!(mesh.iter_faces().count(), 2 * n - nhull - 2);
+    assert_convex_boundary(&mesh);
+  }
+
+  #[test]
+  fn test_collapse_edges() {
+    let mesh = crate::triangulator::triangulate(&random_points(100));
+    let nfaces = mesh.iter_faces().count();
+    let mut collapsed = 0;
+    let mut inversions = 0;
+
+    for halfedge in mesh.iter_edges() {
+      let mut copy = mesh.clone();
+      let u = mesh.vertex(mesh.twin(halfedge));
+      let w = mesh.vertex(halfedge);
+      let border = mesh.is_border_edge(halfedge);
+      match copy.collapse_edge(halfedge) {
+        Ok(v) => {
+          assert!(v == u || v == w);
+          assert_eq!(copy.iter_vertices().count(), 99);
+          assert_eq!(copy.iter_faces().count(), if border { nfaces - 1 } else { nfaces - 2 });
+          assert!(!mesh.is_boundary_vertex(u) || !mesh.is_boundary_vertex(w) || border);
+          assert!(copy.is_boundary_vertex(v) == (mesh.is_boundary_vertex(u) || mesh.is_boundary_vertex(w)));
+          assert_valid(&copy);
+          collapsed += 1;
+        },
+        Err(CollapseError::Inversion) => inversions += 1,
+        Err(CollapseError::Boundary) => assert!(mesh.is_boundary_vertex(u) && mesh.is_boundary_vertex(w) && !border),
+        Err(CollapseError::LinkCondition) => {},
+      }
+    }
+    assert!(collapsed > 0);
+    assert!(inversions > 0);
+  }
+
+  #[test]
+  fn test_collapse_keeps_border_vertex() {
+    // the inner vertex 5 is connected to all vertices of the convex hull
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 2.0, y: -0.5},
+      Point {x: 4.0, y: 0.0},
+      Point {x: 4.0, y: 4.0},
+      Point {x: 0.0, y: 4.0},
+      Point {x: 2.0, y: 1.0},
+    ];
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let (border_vertex, inner_vertex) = (1, 5);
+    assert_eq!(mesh.valence(inner_vertex), 5);
+    let halfedge = mesh.iter_edges()
+      .find(|&h| mesh.vertex(mesh.twin(h)) == border_vertex && mesh.vertex(h) == inner_vertex)
+      .unwrap();
+
+    // collapse from the border into the interior
+    let v = mesh.collapse_edge(halfedge).unwrap();
+    assert_eq!(v, border_vertex);
+    assert!(mesh.point_of_vertex(v).nearly_equals(&points[border_vertex]));
+    assert!(mesh.is_boundary_vertex(v));
+    assert_eq!(mesh.iter_faces().count(), 3);
+    assert_valid(&mesh);
+  }
+
+  #[test]
+  fn test_collapse_link_condition() {
+    let (p1, p2, p3) = equiliteral_triangle(1.0);
+    let mut mesh = Mesh::triangle(p1, p2, p3);
+    let face = mesh.some_face().unwrap();
+    mesh.split_face(face, Point {x: 0.5, y: 0.3});
+
+    let border_edge = mesh.twin(mesh.edge_of_face(mesh.boundary()));
+    assert_eq!(mesh.collapse_edge(border_edge), Err(CollapseError::LinkCondition));
+    assert_valid(&mesh);
+
+    let mut triangle = Mesh::triangle(Point {x: 0.0, y: 0.0}, Point {x: 1.0, y: 0.0}, Point {x: 0.0, y: 1.0});
+    let halfedge = triangle.edge_of_face(triangle.some_face().unwrap());
+    assert_eq!(triangle.collapse_edge(halfedge), Err(CollapseError::LinkCondition));
+  }
+
+  #[test]
+  fn test_compact() {
+    let points = random_points(200);
+    let mut mesh = crate::triangulator::triangulate(&points);
+    for v in (0..200).step_by(3) {
+      mesh.remove_vertex(v);
+    }
+    let nfaces = mesh.iter_faces().count();
+    let nedges = mesh.iter_edges().count();
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+
+    let maps = mesh.compact();
+    assert!(mesh.validate());
+    assert_consistent(&mesh);
+    assert_eq!(mesh.number_of_vertices(), 200 - 67);
+    assert_eq!(mesh.number_of_faces(), nfaces + 1);
+    assert_eq!(mesh.iter_edges().count(), nedges);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), nhull);
+    assert!(mesh.iter_face(mesh.boundary()).all(|h| mesh.face(h) == mesh.boundary()));
+
+    for (old, &new) in maps.vertices.iter().enumerate() {
+      if old % 3 == 0 {
+        assert_eq!(new, EMPTY);
+      } else {
+        assert!(mesh.point_of_vertex(new).nearly_equals(&points[old]));
+      }
+    }
+
+    // compacting twice does not change anything
+    let maps = mesh.compact();
+    assert!(maps.vertices.iter().enumerate().all(|(old, &new)| old == new));
+    assert!(maps.halfedges.iter().enumerate().all(|(old, &new)| old == new));
+    assert!(maps.faces.iter().enumerate().all(|(old, &new)| old == new));
+  }
+
+  #[test]
+  fn test_compact_after_collapses() {
+    let mut mesh = crate::triangulator::triangulate(&random_points(100));
+    let mut collapsed = 0;
+    for halfedge in mesh.iter_edges().collect::<Vec<usize>>() {
+      if mesh.halfedges[halfedge].is_valid() && mesh.collapse_edge(halfedge).is_ok() {
+        collapsed += 1;
+      }
+    }
+    assert!(collapsed > 0);
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+
+    mesh.compact();
+    assert!(mesh.validate());
+    assert_valid(&mesh);
+    let n = mesh.number_of_vertices();
+    assert_eq!(n, 100 - collapsed);
+    assert_eq!(mesh.number_of_faces(), 2 * n - nhull - 2 + 1);
+  }
+
+  #[test]
+  fn test_check_valid_meshes() {
+    let (p1, p2, p3) = equiliteral_triangle(1.0);
+    assert_eq!(Mesh::triangle(p1, p2, p3).check_delaunay(), Ok(()));
+    assert_eq!(Mesh::empty().check(), Ok(()));
+    assert_eq!(crate::triangulator::triangulate(&random_points(300)).check_delaunay(), Ok(()));
+  }
+
+  #[test]
+  fn test_check_detects_corruption() {
+    let mesh = crate::triangulator::triangulate(&random_points(50));
+    let inner = mesh.iter_edges().find(|&h| !mesh.is_border_edge(h)).unwrap();
+
+    let mut broken = mesh.clone();
+    broken.halfedges[inner].twin = mesh.next(inner);
+    assert!(broken.check().unwrap_err().contains(&MeshError::Twin(inner)));
+
+    let mut broken = mesh.clone();
+    broken.halfedges[inner].prev = mesh.next(inner);
+    assert!(broken.check().unwrap_err().contains(&MeshError::NextPrev(inner)));
+
+    let mut broken = mesh.clone();
+    broken.halfedges[inner].face = mesh.face(mesh.twin(inner));
+    assert!(broken.check().unwrap_err().contains(&MeshError::FaceCycle(inner)));
+
+    let mut broken = mesh.clone();
+    let v = mesh.vertex(inner);
+    broken.vertices[v].halfedge = mesh.next(inner);
+    assert_eq!(broken.check(), Err(vec![MeshError::VertexHalfedge(v)]));
+
+    let mut broken = mesh.clone();
+    broken.halfedges[inner].next = broken.halfedges.len();
+    assert_eq!(broken.check(), Err(vec![MeshError::DanglingHalfedge(inner)]));
+
+    let mut broken = mesh.clone();
+    broken.faces[mesh.face(inner)].id = EMPTY;
+    assert_eq!(broken.check(), Err(vec![MeshError::FaceId(mesh.face(inner))]));
+
+    // move a vertex such that its triangles are inverted
+    let mut broken = mesh.clone();
+    let opposite = broken.point_of_edge(broken.twin(inner)).clone();
+    let p = broken.point_of_edge(inner).clone();
+    broken.vertices[v].point = Point {x: 2.0 * opposite.x - p.x, y: 2.0 * opposite.y - p.y};
+    assert!(broken.check().unwrap_err().iter().any(|e| matches!(e, MeshError::Orientation(_))));
+
+    // a second face claiming the cycle of another face
+    let mut broken = mesh.clone();
+    let face = broken.faces.len();
+    broken.faces.push(Face::new(face, mesh.edge_of_face(mesh.face(inner)), FaceType::Hole));
+    assert!(broken.check().is_err());
+  }
+
+  #[test]
+  fn test_check_delaunay() {
+    let mut mesh = crate::triangulator::triangulate(&random_points(50));
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+    let inner = mesh.iter_edges().find(|&h| !mesh.is_border_edge(h) && {
+      let a = mesh.point_of_edge(mesh.next(h));
+      let b = mesh.point_of_edge(mesh.next(mesh.twin(h)));
+      a.orient(b, mesh.point_of_edge(h)) * a.orient(b, mesh.point_of_edge(mesh.twin(h))) < 0.0
+    }).unwrap();
+    mesh.flip(inner);
+    assert_eq!(mesh.check(), Ok(()));
+    assert_eq!(mesh.check_delaunay(), Err(vec![MeshError::NotDelaunay(inner.min(mesh.twin(inner)))]));
+  }
+
+  #[test]
+  fn test_vertex_circulators() {
+    let (p1, p2, p3) = equiliteral_triangle(1.0);
+    let mut mesh = Mesh::triangle(p1, p2, p3);
+    let face = mesh.some_face().unwrap();
+    let center = mesh.split_face(face, Point {x: 0.5, y: 0.3});
+
+    assert_eq!(mesh.valence(center), 3);
+    assert_eq!(mesh.iter_incident_faces(center).count(), 3);
+    assert!(!mesh.is_boundary_vertex(center));
+    assert!(mesh.iter_incoming_edges(center).all(|h| mesh.vertex(h) == center));
+    assert!(mesh.iter_outgoing_edges(center).all(|h| mesh.vertex(mesh.twin(h)) == center));
+
+    for corner in mesh.iter_adjacent_vertices(center).collect::<Vec<usize>>() {
+      assert_eq!(mesh.valence(corner), 3);
+      assert_eq!(mesh.iter_incident_faces(corner).count(), 2);
+      assert!(mesh.is_boundary_vertex(corner));
+      assert!(mesh.iter_adjacent_vertices(corner).any(|v| v == center));
+    }
+  }
+
+  #[test]
+  fn test_vertex_circulators_random() {
+    let mesh = crate::triangulator::triangulate(&random_points(200));
+    let valences: usize = mesh.iter_vertices().map(|v| mesh.valence(v)).sum();
+    let incident: usize = mesh.iter_vertices().map(|v| mesh.iter_incident_faces(v).count()).sum();
+    assert_eq!(valences, mesh.iter_edges().count());
+    assert_eq!(incident, 3 * mesh.iter_faces().count());
+
+    let border: Vec<usize> = mesh.iter_face(mesh.boundary()).map(|h| mesh.vertex(h)).collect();
+    for v in mesh.iter_vertices() {
+      assert_eq!(mesh.is_boundary_vertex(v), border.contains(&v));
+      let neighbours: Vec<usize> = mesh.iter_adjacent_vertices(v).collect();
+      assert_eq!(mesh.iter_incident_faces(v).count(), if border.contains(&v) { neighbours.len() - 1 } else { neighbours.len() });
+
+      // neighbours of inner vertices are ordered clockwise
+      if !border.contains(&v) {
+        let p = mesh.point_of_vertex(v);
+        for i in 0..neighbours.len() {
+          let a = mesh.point_of_vertex(neighbours[i]);
+          let b = mesh.point_of_vertex(neighbours[(i + 1) % neighbours.len()]);
+          assert!(p.orient(a, b) < 0.0);
+        }
+      }
+    }
+  }
+
+  fn assert_segment(mesh: &Mesh, halfedges: &[usize], v1: usize, v2: usize) {
+    let p1 = mesh.point_of_vertex(v1);
+    let p2 = mesh.point_of_vertex(v2);
+    assert_eq!(mesh.vertex(mesh.twin(halfedges[0])), v1);
+    assert_eq!(mesh.vertex(*halfedges.last().unwrap()), v2);
+    for (i, &h) in halfedges.iter().enumerate() {
+      assert!(mesh.is_constrained(h) && mesh.is_constrained(mesh.twin(h)));
+      assert_eq!(p1.orient(p2, mesh.point_of_edge(h)), 0.0);
+      if i > 0 {
+        assert_eq!(mesh.vertex(mesh.twin(h)), mesh.vertex(halfedges[i - 1]));
+      }
+    }
+  }
+
+  #[test]
+  fn test_insert_constraints() {
+    let mut mesh = crate::triangulator::triangulate(&random_points(300));
+    let mut inserted = 0;
+    for i in 0..40 {
+      let (v1, v2) = ((i * 37) % 300, (i * 101 + 7) % 300);
+      match mesh.insert_constraint(v1, v2) {
+        Ok(halfedges) => {
+          assert_segment(&mesh, &halfedges, v1, v2);
+          inserted += 1;
+        },
+        Err(error) => assert!(matches!(error, ConstraintError::Crossing | ConstraintError::Outside | ConstraintError::Degenerate)),
+      }
+      assert_consistent(&mesh);
+    }
+    assert!(inserted > 0);
+  }
+
+  #[test]
+  fn test_constraint_through_vertices() {
+    let mut points = Vec::new();
+    for i in 0..5 {
+      for j in 0..5 {
+        points.push(Point {x: i as f64, y: j as f64});
+      }
+    }
+    let mut mesh = crate::triangulator::triangulate(&points);
+    let halfedges = mesh.insert_constraint(0, 24).unwrap();
+    assert_eq!(halfedges.len(), 4);
+    assert_segment(&mesh, &halfedges, 0, 24);
+    assert_consistent(&mesh);
+
+    // the other diagonal shares the center vertex but does not cross any constrained edge
+    let halfedges = mesh.insert_constraint(4, 20).unwrap();
+    assert_eq!(halfedges.len(), 4);
+    assert_segment(&mesh, &halfedges, 4, 20);
+    assert_eq!(mesh.insert_constraint(5, 1), Err(ConstraintError::Crossing));
+    assert_eq!(mesh.insert_constraint(3, 3), Err(ConstraintError::Degenerate));
+  }
+
+  #[test]
+  fn test_constraints_are_kept() {
+    let mut mesh = crate::triangulator::triangulate(&random_points(100));
+    let halfedges = mesh.insert_constraint(3, 42).unwrap();
+    assert_segment(&mesh, &halfedges, 3, 42);
+
+    // split the constrained edge and insert points close to it
+    let h = halfedges[0];
+    let p = mesh.point_of_edge(mesh.twin(h)).center(mesh.point_of_edge(h));
+    let v = mesh.split_edge(h, p);
+    assert!(mesh.iter_incoming_edges(v).filter(|&e| mesh.is_constrained(e)).count() == 2);
+    for e in halfedges.iter().skip(1) {
+      let face = mesh.face(*e);
+      let center = mesh.iter_face(face).map(|e| mesh.point_of_edge(e).clone()).fold(Point {x: 0.0, y: 0.0}, |c, p| c.add(&p.div(3.0)));
+      mesh.split_face(face, center);
+    }
+    assert_valid(&mesh);
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+    assert!(halfedges.iter().skip(1).all(|&e| mesh.is_constrained(e)));
+  }
+
+  #[test]
+  fn test_split_constrained_edges() {
+    for seed in 0..10 {
+      let mut mesh = crate::triangulator::triangulate(&seeded_points(200, seed, 10.0));
+      for i in 0..20 {
+        let _ = mesh.insert_constraint((i * 37) % 200, (i * 101 + 7) % 200);
+      }
+
+      let constrained: Vec<usize> = (0..mesh.halfedges.len())
+        .filter(|&h| mesh.halfedges[h].is_valid() && mesh.is_constrained(h) && h < mesh.twin(h))
+        .collect();
+      for h in constrained {
+        let p = mesh.point_of_edge(mesh.twin(h)).center(mesh.point_of_edge(h));
+        mesh.split_edge(h, p);
+      }
+
+      // both sides of every edge agree and the constrained Delaunay property holds
+      for h in (0..mesh.halfedges.len()).filter(|&h| mesh.halfedges[h].is_valid()) {
+        assert_eq!(mesh.is_constrained(h), mesh.is_constrained(mesh.twin(h)));
+      }
+      assert_consistent(&mesh);
+    }
+  }
+
+  fn grid(n: usize) -> Mesh {
+    let mut points = Vec::new();
+    for i in 0..n {
+      for j in 0..n {
+        points.push(Point {x: i as f64, y: j as f64});
+      }
+    }
+    crate::triangulator::triangulate(&points)
+  }
+
+  #[test]
+  fn test_carve_polygon_hole() {
+    let mut mesh = grid(10);
+    let hole = mesh.carve_polygon_hole(&[33, 63, 66, 36]).unwrap();
+    assert_eq!(mesh.holes(), &[hole]);
+    assert_eq!(mesh.iter_face(hole).count(), 12);
+    assert!(mesh.iter_face(hole).all(|h| mesh.is_border(h) && !mesh.is_border(mesh.twin(h))));
+    assert!(mesh.is_boundary_vertex(33) && !mesh.is_boundary_vertex(22));
+    assert_eq!(mesh.iter_vertices().count(), 100 - 4);
+    assert_eq!(mesh.iter_faces().count(), 2 * 9 * 9 - 2 * 3 * 3);
+    assert_consistent(&mesh);
+
+    mesh.compact();
+    assert_eq!(mesh.holes().len(), 1);
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_remove_hole_vertex() {
+    // the side 33 -> 36 of the hole passes (almost) exactly through 34 and 35
+    let mut points = Vec::new();
+    for i in 0..10 {
+      for j in 0..10 {
+        let d = if i == 3 { 0.0005 * (j as f64 - 3.0).abs() } else { 0.0 };
+        points.push(Point {x: i as f64 + d, y: j as f64 + d});
+      }
+    }
+    for v in [33, 63, 66, 36, 0, 5, 99] {
+      let mut mesh = crate::triangulator::triangulate(&points);
+      mesh.carve_polygon_hole(&[33, 63, 66, 36]).unwrap();
+      mesh.remove_vertex(v);
+      assert_consistent(&mesh);
+    }
+  }
+
+  #[test]
+  fn test_carve_hole_by_seed() {
+    let mut mesh = grid(10);
+    assert_eq!(mesh.carve_hole(&Point {x: 4.3, y: 4.6}), Err(HoleError::Border));
+    assert_eq!(mesh.carve_hole(&Point {x: -1.0, y: 4.6}), Err(HoleError::Outside));
+
+    // cw order
+    for (v1, v2) in [(22, 25), (25, 55), (55, 52), (52, 22)] {
+      mesh.insert_constraint(v1, v2).unwrap();
+    }
+    let hole = mesh.carve_hole(&Point {x: 4.3, y: 4.6}).unwrap();
+    assert_eq!(mesh.iter_face(hole).count(), 12);
+    assert_eq!(mesh.iter_vertices().count(), 100 - 4);
+    assert_consistent(&mesh);
+
+    // the hole may neither touch the border nor another hole
+    assert_eq!(mesh.carve_polygon_hole(&[55, 58, 88, 85]), Err(HoleError::Border));
+    assert_eq!(mesh.carve_polygon_hole(&[7, 8, 18]), Err(HoleError::Border));
+    assert_consistent(&mesh);
+  }
+
+  #[test]
+  fn test_fill_hole() {
+    let mut mesh = grid(10);
+    let hole = mesh.carve_polygon_hole(&[33, 63, 66, 36]).unwrap();
+    let nfaces = mesh.iter_faces().count();
+
+    // insert a point into the hole
+    let p = Point {x: 4.5, y: 4.5};
+    let halfedge = mesh.find_visible_border_edge(hole, &p).unwrap();
+    let t2 = mesh.insert(halfedge, p);
+    assert_eq!(mesh.face(t2), hole);
+    assert_eq!(mesh.iter_face(hole).count(), 13);
+    assert_eq!(mesh.iter_faces().count(), nfaces + 1);
+    assert_valid(&mesh);
+
+    // close the hole by connecting all concave corners
+    let mut halfedge = t2;
+    while halfedge != EMPTY {
+      let corner = mesh.iter_face(hole).find(|&h| {
+        let a = mesh.point_of_edge(mesh.prev(h));
+        let b = mesh.point_of_edge(h);
+        let c = mesh.point_of_edge(mesh.next(h));
+        a.orient(b, c) > 0.0 && mesh.iter_face(hole).map(|e| mesh.point_of_edge(e)).all(|q| {
+          std::ptr::eq(q, a) || std::ptr::eq(q, b) || std::ptr::eq(q, c) ||
+          a.orient(b, q) < 0.0 || b.orient(c, q) < 0.0 || c.orient(a, q) < 0.0
+        })
+      }).unwrap();
+      halfedge = mesh.connect(corner);
+      assert_valid(&mesh);
+    }
+    assert!(mesh.holes().is_empty());
+    assert!(mesh.is_normal(hole));
+    assert_eq!(mesh.iter_faces().count(), nfaces + 1 + 11);
+  }
+
+  #[test]
+  fn test_from_triangulation() {
+    let points: Vec<delaunator::Point> = random_points(200).iter().map(|p| delaunator::Point {x: p.x, y: p.y}).collect();
+    let triangulation = delaunator::triangulate(&points);
+    let mesh = Mesh::from_triangulation(&points, &triangulation);
+    assert_consistent(&mesh);
+    assert_eq!(mesh.iter_faces().count(), triangulation.len());
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), triangulation.hull.len());
+    assert!(mesh.holes().is_empty());
+
+    let result = mesh.to_triangulation();
+    assert_eq!(result.triangles, triangulation.triangles);
+    assert_eq!(result.halfedges, triangulation.halfedges);
+    assert_eq!(result.hull, triangulation.hull);
+  }
+
+  #[test]
+  fn test_from_removed_triangles() {
+    let points = random_points(200);
+    let mesh = crate::triangulator::triangulate(&points);
+    let triangles = mesh.to_triangles();
+    assert_eq!(triangles.len(), 3 * mesh.iter_faces().count());
+
+    // remove all triangles within a disc in the center and at the left side
+    let removed = |t: &[usize]| {
+      let c = Point {x: (points[t[0]].x + points[t[1]].x + points[t[2]].x) / 3.0, y: (points[t[0]].y + points[t[1]].y + points[t[2]].y) / 3.0};
+      c.distance(&Point {x: 5.0, y: 5.0}) < 2.0 || c.x < 1.0
+    };
+    let remaining: Vec<usize> = triangles.chunks_exact(3).filter(|t| !removed(t)).flatten().copied().collect();
+    assert!(remaining.len() < triangles.len());
+
+    let mesh = Mesh::from_triangles(&points, &remaining);
+    assert_valid(&mesh);
+    assert_eq!(mesh.holes().len(), 1);
+    assert_eq!(mesh.to_triangles(), remaining);
+    assert!(mesh.iter_face(mesh.holes()[0]).all(|h| mesh.point_of_edge(h).distance(&Point {x: 5.0, y: 5.0}) < 4.0));
+
+    // cw triangles are accepted as well
+    let reversed: Vec<usize> = remaining.chunks_exact(3).flat_map(|t| [t[0], t[2], t[1]]).collect();
+    assert_eq!(Mesh::from_triangles(&points, &reversed).check(), Ok(()));
+  }
+
+  #[test]
+  fn test_make_delaunay() {
+    let mut mesh = grid(10);
+    let offsets = random_points(100);
+    for (v, offset) in offsets.iter().enumerate() {
+      let p = mesh.point_of_vertex(v);
+      let q = Point {x: p.x + (offset.x - 5.0) * 0.05, y: p.y + (offset.y - 5.0) * 0.05};
+      mesh.move_vertex(v, q);
+    }
+    assert!(mesh.inverted_faces().is_empty());
+    assert!(mesh.make_delaunay() > 0);
+    assert_consistent(&mesh);
+    assert_eq!(mesh.make_delaunay(), 0);
+
+    let p = mesh.point_of_vertex(44).clone();
+    mesh.move_vertex(44, Point {x: p.x + 2.0, y: p.y});
+    assert!(!mesh.inverted_faces().is_empty());
+  }
+
+  #[test]
+  fn test_convexify() {
+    let points = random_points(200);
+    let mesh = crate::triangulator::triangulate(&points);
+    let nfaces = mesh.iter_faces().count();
+
+    // cut a notch into the bottom of the triangulation
+    let remaining: Vec<usize> = mesh.to_triangles().chunks_exact(3).filter(|t| {
+      let c = Point {x: (points[t[0]].x + points[t[1]].x + points[t[2]].x) / 3.0, y: (points[t[0]].y + points[t[1]].y + points[t[2]].y) / 3.0};
+      c.distance(&Point {x: 5.0, y: 0.0}) > 3.0
+    }).flatten().copied().collect();
+    let mut mesh = Mesh::from_triangles(&points, &remaining);
+    assert!(mesh.iter_faces().count() < nfaces);
+
+    assert!(mesh.convexify() > 0);
+    assert_valid(&mesh);
+    assert_convex_boundary(&mesh);
+    mesh.make_delaunay();
+    assert_consistent(&mesh);
+
+    // Euler: the convex triangulation of the remaining n points with h points on the hull consists of 2n - h - 2 triangles
+    let mut vertices = remaining.clone();
+    vertices.sort();
+    vertices.dedup();
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+    assert_eq!(mesh.iter_faces().count(), 2 * vertices.len() - nhull - 2);
+  }
+
+  #[test]
+  fn test_remove_border_face() {
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 2.0, y: 0.0},
+      Point {x: 1.0, y: 0.5},
+      Point {x: 3.0, y: 1.0},
+      Point {x: 1.0, y: 2.0},
+      Point {x: -1.0, y: 1.0},
+    ];
+    let mut mesh = Mesh::from_triangles(&points, &[0, 1, 2, 1, 3, 2, 3, 4, 2, 4, 5, 2, 5, 0, 2]);
+    assert_valid(&mesh);
+
+    // the inner vertex crosses the border edge
+    mesh.move_vertex(2, Point {x: 1.0, y: -0.1});
+    let inverted = mesh.inverted_faces();
+    assert_eq!(inverted.len(), 1);
+    assert!(mesh.remove_border_face(inverted[0]));
+    assert_valid(&mesh);
+    assert_eq!(mesh.iter_faces().count(), 4);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 6);
+    assert!(mesh.is_boundary_vertex(2));
+
+    // the opposite vertex is now part of the border
+    let face = mesh.iter_faces().next().unwrap();
+    assert!(!mesh.remove_border_face(face));
+    assert_valid(&mesh);
+  }
+}
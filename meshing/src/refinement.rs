@@ -0,0 +1,444 @@
+use std::collections::VecDeque;
+use crate::geometry::{DSPoint, Point};
+use crate::mesh::{Location, Mesh, EMPTY};
+
+pub type SizeFn = Box<dyn Fn(&Point) -> f64>;
+
+/// Configures a [`Refinement`], i.e. the quality criteria a triangle has to satisfy.
+pub struct RefinementBuilder {
+  min_angle: f64,
+  max_area: Option<f64>,
+  size_fn: Option<SizeFn>,
+  min_edge_len: Option<f64>,
+  max_vertices: usize,
+}
+
+impl Default for RefinementBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl RefinementBuilder {
+  pub fn new() -> Self {
+    RefinementBuilder {
+      min_angle: 20.0,
+      max_area: None,
+      size_fn: None,
+      min_edge_len: None,
+      max_vertices: 1_000_000,
+    }
+  }
+
+  /// The minimum angle (in degrees) of each triangle. Termination is only guaranteed for angles up to about 20.7 degrees
+  /// and if the segments of the mesh do not meet at small angles.
+  pub fn min_angle(mut self, min_angle: f64) -> Self {
+    self.min_angle = min_angle;
+    self
+  }
+
+  /// The maximum area of each triangle.
+  pub fn max_area(mut self, max_area: f64) -> Self {
+    self.max_area = Some(max_area);
+    self
+  }
+
+  /// The maximum edge length of a triangle as a function of its centroid.
+  pub fn size_fn(mut self, size_fn: SizeFn) -> Self {
+    self.size_fn = Some(size_fn);
+    self
+  }
+
+  /// Triangles whose shortest edge and segments which are shorter than this length are not refined any further.
+  /// This guarantees termination if segments meet at small angles. Defaults to 1e-6 times the diameter of the mesh.
+  pub fn min_edge_len(mut self, min_edge_len: f64) -> Self {
+    self.min_edge_len = Some(min_edge_len);
+    self
+  }
+
+  /// The maximum number of vertices inserted by the refinement.
+  pub fn max_vertices(mut self, max_vertices: usize) -> Self {
+    self.max_vertices = max_vertices;
+    self
+  }
+
+  pub fn build(self) -> Refinement {
+    assert!(self.min_angle >= 0.0 && self.min_angle < 60.0);
+    if let Some(max_area) = self.max_area {
+      assert!(max_area > 0.0);
+    }
+
+    let sin_min_angle = self.min_angle.to_radians().sin();
+    Refinement {
+      min_sin_sq: sin_min_angle * sin_min_angle,
+      max_area: self.max_area,
+      size_fn: self.size_fn,
+      min_edge_len: self.min_edge_len,
+      max_vertices: self.max_vertices,
+    }
+  }
+}
+
+/// Ruppert's **Delaunay refinement** of a (constrained) Delaunay [`Mesh`].
+///
+/// Segments, i.e. constrained edges and edges of the boundary or of a hole, which are encroached (a vertex lies inside their
+/// diametral circle) are split at their midpoint. Afterwards the circumcenter of each bad triangle, i.e. a triangle which is too skinny
+/// or too large, is inserted unless it would encroach a segment, in which case the segment is split instead.
+///
+/// # Examples
+///
+/// ```
+/// use meshing::geometry::Point;
+/// use meshing::refinement::RefinementBuilder;
+/// use meshing::triangulator::triangulate;
+///
+/// let points = vec![
+///   Point { x: 0., y: 0. },
+///   Point { x: 1., y: 0. },
+///   Point { x: 1., y: 1. },
+///   Point { x: 0., y: 1. },
+/// ];
+/// let mut mesh = triangulate(&points);
+/// let refinement = RefinementBuilder::new().min_angle(25.0).max_area(0.01).build();
+/// refinement.refine(&mut mesh);
+/// assert!(mesh.iter_faces().count() >= 100);
+/// ```
+pub struct Refinement {
+  min_sin_sq: f64,
+  max_area: Option<f64>,
+  size_fn: Option<SizeFn>,
+  min_edge_len: Option<f64>,
+  max_vertices: usize,
+}
+
+impl Refinement {
+
+  /// Refines the ```mesh``` until all triangles satisfy the quality criteria or the maximum number of vertices is reached.
+  /// Returns the number of inserted vertices. Rejected circumcenters are removed again, therefore, the mesh may contain
+  /// destroyed elements afterwards (see [`Mesh::compact`]).
+  pub fn refine(&self, mesh: &mut Mesh) -> usize {
+    let min_len = self.min_edge_len.unwrap_or_else(|| 1e-6 * diameter(mesh));
+    let splittable = |mesh: &Mesh, h: usize| mesh.point_of_edge(h).distance(mesh.point_of_edge(mesh.twin(h))) >= 2.0 * min_len;
+    let mut segments: VecDeque<usize> = mesh.iter_edges().filter(|&h| is_segment(mesh, h)).collect();
+    let mut faces: VecDeque<usize> = VecDeque::new();
+    let mut inserted = 0;
+    let mut scanned = EMPTY;
+
+    while inserted < self.max_vertices {
+      if let Some(segment) = segments.pop_front() {
+        if is_encroached(mesh, segment) && splittable(mesh, segment) {
+          let v = split_segment(mesh, segment);
+          inserted += 1;
+          enqueue(mesh, v, &mut segments, &mut faces);
+        }
+        continue;
+      }
+
+      let face = match faces.pop_front() {
+        Some(face) => face,
+        None if scanned == inserted => break,
+        None => {
+          // make sure that no bad triangle has been missed, e.g., because it has been replaced by removing a rejected circumcenter
+          scanned = inserted;
+          faces.extend(mesh.iter_faces().filter(|&face| self.is_bad(mesh, face, min_len)));
+          match faces.pop_front() {
+            Some(face) => face,
+            None => break,
+          }
+        },
+      };
+      if !mesh.is_normal(face) || !self.is_bad(mesh, face, min_len) {
+        continue;
+      }
+
+      let c = circumcenter(mesh, face);
+      let v = match mesh.locate_from(&c, face) {
+        Location::InFace(f) => mesh.split_face(f, c),
+        Location::OnEdge(h) if !is_segment(mesh, h) => mesh.split_edge(h, c),
+        Location::OnEdge(h) => {
+          if splittable(mesh, h) {
+            split_segment_and_enqueue(mesh, h, &mut segments, &mut faces);
+            inserted += 1;
+          }
+          continue;
+        },
+        Location::OnVertex(_) => continue,
+        Location::Outside(h) => {
+          // the circumcenter lies outside of the mesh, therefore, it encroaches the first segment between the triangle and it
+          let segment = first_segment_towards(mesh, face, &c).unwrap_or(h);
+          if splittable(mesh, segment) {
+            split_segment_and_enqueue(mesh, segment, &mut segments, &mut faces);
+            inserted += 1;
+            faces.push_back(face);
+          }
+          continue;
+        },
+      };
+
+      // reject the circumcenter if it encroaches a segment of its star
+      let encroached: Vec<usize> = mesh.iter_incoming_edges(v)
+        .filter(|&h| mesh.is_normal(mesh.face(h)))
+        .map(|h| mesh.prev(h))
+        .filter(|&h| is_segment(mesh, h) && encroaches(mesh, h, mesh.point_of_vertex(v)))
+        .collect();
+      if encroached.is_empty() {
+        inserted += 1;
+        enqueue(mesh, v, &mut segments, &mut faces);
+      } else {
+        mesh.remove_vertex(v);
+        for h in encroached {
+          if inserted >= self.max_vertices {
+            break;
+          }
+          if !splittable(mesh, h) {
+            continue;
+          }
+          split_segment_and_enqueue(mesh, h, &mut segments, &mut faces);
+          inserted += 1;
+        }
+      }
+    }
+    inserted
+  }
+
+  /// Returns **true** if and only if the triangle ```face``` violates one of the quality criteria
+  /// and its shortest edge is not shorter than ```min_len```.
+  fn is_bad(&self, mesh: &Mesh, face: usize, min_len: f64) -> bool {
+    let points: Vec<&Point> = mesh.iter_face(face).map(|h| mesh.point_of_edge(h)).collect();
+    let (a, b, c) = (points[0], points[1], points[2]);
+    let edges_sq = [a.distance_sq(b), b.distance_sq(c), c.distance_sq(a)];
+    let min_sq = edges_sq.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_sq = edges_sq.iter().cloned().fold(0.0, f64::max);
+    if min_sq < min_len * min_len {
+      return false;
+    }
+
+    // sin(min angle) = shortest edge / (2 * circumradius)
+    if min_sq < 4.0 * a.circumradius_sq(b, c) * self.min_sin_sq {
+      return true;
+    }
+    if self.max_area.is_some_and(|max_area| a.orient(b, c).abs() / 2.0 > max_area) {
+      return true;
+    }
+    if let Some(size_fn) = self.size_fn.as_ref() {
+      let centroid = Point {x: (a.x + b.x + c.x) / 3.0, y: (a.y + b.y + c.y) / 3.0};
+      let h = size_fn(&centroid);
+      if max_sq > h * h {
+        return true;
+      }
+    }
+    false
+  }
+}
+
+/// Returns **true** if and only if the edge of ```halfedge``` is a segment, i.e. constrained or part of the border.
+fn is_segment(mesh: &Mesh, halfedge: usize) -> bool {
+  mesh.is_constrained(halfedge) || mesh.is_border(halfedge) || mesh.is_border(mesh.twin(halfedge))
+}
+
+/// Returns **true** if and only if ```p``` lies strictly inside the diametral circle of the segment ```halfedge```.
+fn encroaches(mesh: &Mesh, halfedge: usize, p: &Point) -> bool {
+  let a = mesh.point_of_edge(mesh.twin(halfedge)).subtract(p);
+  let b = mesh.point_of_edge(halfedge).subtract(p);
+  a.x * b.x + a.y * b.y < 0.0
+}
+
+/// Returns **true** if and only if the segment ```halfedge``` is encroached by the apex of one of its triangles.
+fn is_encroached(mesh: &Mesh, halfedge: usize) -> bool {
+  [halfedge, mesh.twin(halfedge)].iter()
+    .filter(|&&h| mesh.is_normal(mesh.face(h)))
+    .any(|&h| encroaches(mesh, halfedge, mesh.point_of_edge(mesh.next(h))))
+}
+
+/// Walks from the centroid of ```face``` straight towards ```p``` and returns the first segment crossed by the walk,
+/// or ```None``` if ```p``` is reached before any segment.
+fn first_segment_towards(mesh: &Mesh, face: usize, p: &Point) -> Option<usize> {
+  let points: Vec<&Point> = mesh.iter_face(face).map(|h| mesh.point_of_edge(h)).collect();
+  let o = Point {x: (points[0].x + points[1].x + points[2].x) / 3.0, y: (points[0].y + points[1].y + points[2].y) / 3.0};
+  let mut face = face;
+  let mut from = EMPTY;
+
+  'walk: loop {
+    for h in mesh.iter_face(face).filter(|&h| h != from) {
+      let u1 = mesh.point_of_edge(mesh.twin(h));
+      let u2 = mesh.point_of_edge(h);
+      // the walk leaves the face through h, if p lies beyond h and the line o -> p passes between u1 and u2
+      if u1.orient(u2, p) < 0.0 && o.orient(p, u1) <= 0.0 && o.orient(p, u2) >= 0.0 {
+        if is_segment(mesh, h) {
+          return Some(h);
+        }
+        from = mesh.twin(h);
+        face = mesh.face(from);
+        continue 'walk;
+      }
+    }
+    return None;
+  }
+}
+
+fn split_segment(mesh: &mut Mesh, halfedge: usize) -> usize {
+  let p = mesh.point_of_edge(mesh.twin(halfedge)).center(mesh.point_of_edge(halfedge));
+  mesh.split_edge(halfedge, p)
+}
+
+fn split_segment_and_enqueue(mesh: &mut Mesh, halfedge: usize, segments: &mut VecDeque<usize>, faces: &mut VecDeque<usize>) {
+  let v = split_segment(mesh, halfedge);
+  enqueue(mesh, v, segments, faces);
+}
+
+/// Schedules all segments and triangles around the new vertex ```v``` for an inspection.
+fn enqueue(mesh: &Mesh, v: usize, segments: &mut VecDeque<usize>, faces: &mut VecDeque<usize>) {
+  for h in mesh.iter_incoming_edges(v) {
+    if is_segment(mesh, h) {
+      segments.push_back(h);
+    }
+    if mesh.is_normal(mesh.face(h)) {
+      faces.push_back(mesh.face(h));
+      let opposite = mesh.prev(h);
+      if is_segment(mesh, opposite) {
+        segments.push_back(opposite);
+      }
+    }
+  }
+}
+
+fn diameter(mesh: &Mesh) -> f64 {
+  let (mut min, mut max) = (Point {x: f64::INFINITY, y: f64::INFINITY}, Point {x: f64::NEG_INFINITY, y: f64::NEG_INFINITY});
+  for v in mesh.iter_vertices() {
+    let p = mesh.point_of_vertex(v);
+    min = Point {x: min.x.min(p.x), y: min.y.min(p.y)};
+    max = Point {x: max.x.max(p.x), y: max.y.max(p.y)};
+  }
+  if min.x > max.x { 0.0 } else { min.distance(&max) }
+}
+
+fn circumcenter(mesh: &Mesh, face: usize) -> Point {
+  let points: Vec<&Point> = mesh.iter_face(face).map(|h| mesh.point_of_edge(h)).collect();
+  points[0].circumcenter(points[1], points[2])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::triangulator::triangulate;
+  use crate::testing::{seeded_points, SeededRandom};
+
+  fn min_angle(mesh: &Mesh, face: usize) -> f64 {
+    let points: Vec<&Point> = mesh.iter_face(face).map(|h| mesh.point_of_edge(h)).collect();
+    (0..3).map(|i| {
+      let u = points[(i + 1) % 3].subtract(points[i]);
+      let v = points[(i + 2) % 3].subtract(points[i]);
+      ((u.x * v.x + u.y * v.y) / (u.len() * v.len())).acos().to_degrees()
+    }).fold(180.0, f64::min)
+  }
+
+  fn split_at(mesh: &mut Mesh, p: Point) -> usize {
+    match mesh.locate(&p) {
+      Location::InFace(face) => mesh.split_face(face, p),
+      location => panic!("unexpected location {:?}", location),
+    }
+  }
+
+  fn square_with_points(n: usize) -> Mesh {
+    seeded_square(n, 7)
+  }
+
+  fn seeded_square(n: usize, seed: u64) -> Mesh {
+    let mut points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 10.0, y: 0.0},
+      Point {x: 10.0, y: 10.0},
+      Point {x: 0.0, y: 10.0},
+    ];
+    points.extend(seeded_points(n, seed, 10.0));
+    triangulate(&points)
+  }
+
+  #[test]
+  fn test_min_angle() {
+    let mut mesh = square_with_points(50);
+    assert!(mesh.iter_faces().any(|face| min_angle(&mesh, face) < 20.0));
+
+    let inserted = RefinementBuilder::new().min_angle(25.0).build().refine(&mut mesh);
+    assert!(inserted > 0);
+    assert!(mesh.iter_faces().all(|face| min_angle(&mesh, face) >= 25.0 - 1e-9));
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+  }
+
+  #[test]
+  fn test_max_area_and_size_fn() {
+    let mut mesh = square_with_points(0);
+    RefinementBuilder::new().max_area(0.5).build().refine(&mut mesh);
+    assert!(mesh.iter_faces().all(|face| {
+      let p: Vec<&Point> = mesh.iter_face(face).map(|h| mesh.point_of_edge(h)).collect();
+      p[0].orient(p[1], p[2]) / 2.0 <= 0.5
+    }));
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+
+    let mut mesh = square_with_points(0);
+    let size_fn: SizeFn = Box::new(|p: &Point| 0.5 + 0.2 * p.x);
+    RefinementBuilder::new().size_fn(size_fn).build().refine(&mut mesh);
+    let n_left = mesh.iter_vertices().filter(|&v| mesh.point_of_vertex(v).x < 5.0).count();
+    let n_right = mesh.iter_vertices().filter(|&v| mesh.point_of_vertex(v).x >= 5.0).count();
+    assert!(n_left > 2 * n_right);
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+  }
+
+  #[test]
+  fn test_constraints_and_holes() {
+    let mut mesh = square_with_points(30);
+    mesh.insert_constraint(0, 2).unwrap();
+    let n = mesh.number_of_vertices();
+    let hole = {
+      let a = split_at(&mut mesh, Point {x: 2.0, y: 7.0});
+      let b = split_at(&mut mesh, Point {x: 3.0, y: 7.0});
+      let c = split_at(&mut mesh, Point {x: 2.5, y: 8.0});
+      mesh.carve_polygon_hole(&[a, b, c]).unwrap()
+    };
+    assert_eq!(mesh.number_of_vertices(), n + 3);
+
+    RefinementBuilder::new().min_angle(20.0).max_area(1.0).build().refine(&mut mesh);
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+    assert!(mesh.iter_faces().all(|face| min_angle(&mesh, face) >= 20.0 - 1e-9));
+    assert!(mesh.is_border(mesh.edge_of_face(hole)));
+
+    // the diagonal is still represented by constrained edges
+    let constrained = mesh.iter_edges().filter(|&h| mesh.is_constrained(h)).count();
+    assert!(constrained > 2);
+  }
+
+  #[test]
+  fn test_random_constraints() {
+    let constrained = |mesh: &Mesh| mesh.iter_edges().filter(|&h| mesh.is_constrained(h)).count();
+    let refinement = RefinementBuilder::new().min_angle(20.0).max_area(4.0).min_edge_len(0.05).max_vertices(20).build();
+    for seed in 0..40 {
+      let mut mesh = seeded_square(60, seed);
+      let n = mesh.number_of_vertices();
+      let mut random = SeededRandom::new(seed);
+      let mut next = || random.next_index(n);
+      for _ in 0..40 {
+        // constraints crossing previous ones are rejected
+        let _ = mesh.insert_constraint(next(), next());
+      }
+
+      // refine in small steps such that no lost constrained edge goes unnoticed
+      let mut count = constrained(&mesh);
+      while refinement.refine(&mut mesh) > 0 {
+        assert_eq!(mesh.check(), Ok(()));
+        assert_eq!(mesh.check_delaunay(), Ok(()));
+        let new_count = constrained(&mesh);
+        assert!(new_count >= count, "seed {}: {} constrained halfedges after refinement, {} before", seed, new_count, count);
+        count = new_count;
+      }
+    }
+  }
+
+  #[test]
+  fn test_max_vertices() {
+    let mut mesh = square_with_points(0);
+    let inserted = RefinementBuilder::new().max_area(0.01).max_vertices(10).build().refine(&mut mesh);
+    assert_eq!(inserted, 10);
+    assert_eq!(mesh.iter_vertices().count(), 14);
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+  }
+}
@@ -32,21 +32,9 @@ impl Rect {
     pub fn new(center: Point, width: f64, height: f64) -> Self {
         Rect { center, width, height }
     }
-
-    fn x_dist(&self, point: &Point) -> f64 {
-        (self.center.x-point.x).abs() - self.width/2.0
-    }
-
-    fn y_dist(&self, point: &Point) -> f64 {
-        (self.center.y-point.y).abs() - self.height/2.0
-    }
 }
 
 impl SignedDistanceFunction for Rect {
-    /*fn distance(&self, point: &Point) -> f64 {
-        f64::max(self.x_dist(point), self.y_dist(point))
-    }*/
-
     fn distance(&self, point: &Point) -> f64 {
         let dx = (point.x - self.center.x).abs() - self.width/2.0;
         let dy = (point.y - self.center.y).abs() - self.height/2.0;
@@ -60,24 +48,6 @@ impl SignedDistanceFunction for Rect {
             inside_distance
         }
     }
-
-    /*fn grad_with_eps(&self, point: &Point, _: f64) -> Point {
-        let x_dist = self.x_dist(point);
-        let y_dist = self.y_dist(point);
-        if x_dist > y_dist {
-            if self.center.x < point.x {
-                Point {x: 1.0, y: 0.0}
-            } else {
-                Point {x: -1.0, y: 0.0}
-            }
-        } else {
-            if self.center.y < point.y {
-                Point {x: 0.0, y: 1.0}
-            } else {
-                Point {x: 0.0, y: -1.0}
-            }
-        }
-    }*/
 }
 
 #[derive(Clone)]
@@ -0,0 +1,33 @@
+use crate::geometry::Point;
+
+/// Linear congruential generator which produces the same sequence on every platform such that the tests are reproducible.
+pub(crate) struct SeededRandom {
+  state: u64,
+}
+
+impl SeededRandom {
+  pub(crate) fn new(seed: u64) -> Self {
+    SeededRandom {state: seed}
+  }
+
+  fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    self.state
+  }
+
+  /// Returns a number in [0, 1).
+  pub(crate) fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+
+  /// Returns an index in [0, ```n```).
+  pub(crate) fn next_index(&mut self, n: usize) -> usize {
+    ((self.next_u64() >> 33) % n as u64) as usize
+  }
+}
+
+/// Returns ```n``` points distributed in the square [0, ```size```) x [0, ```size```).
+pub(crate) fn seeded_points(n: usize, seed: u64, size: f64) -> Vec<Point> {
+  let mut random = SeededRandom::new(seed);
+  (0..n).map(|_| Point {x: random.next_f64() * size, y: random.next_f64() * size}).collect()
+}
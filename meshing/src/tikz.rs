@@ -44,7 +44,7 @@ fn faces_to_tikz(mesh: &Mesh) -> String {
   
   for face in mesh.iter_faces() {
     tikz.push_str(&face_to_tikz_string(mesh, face));
-    tikz.push_str("\n");
+    tikz.push('\n');
     
     
   }
@@ -79,7 +79,7 @@ fn edges_to_tikz(mesh: &Mesh) -> String {
   let mut tikz: String = String::new();
   for halfedge in mesh.iter_edges() {
     tikz.push_str(&halfedge_to_tikz(mesh, halfedge));
-    tikz.push_str("\n");
+    tikz.push('\n');
   }
   tikz
 }
@@ -116,7 +116,7 @@ fn vertices_to_tikz(mesh: &Mesh) -> String {
     tikz.push_str("\\circleSize");
     tikz.push(')');
     tikz.push(';');
-    tikz.push_str("\n");
+    tikz.push('\n');
   }
   tikz
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+use crate::geometry::{calc_bbox_center, DSPoint, Point};
+use crate::mesh::{Mesh, EMPTY};
+
+/// Returns the **Delaunay triangulation** of ```points``` as a half-edge [`Mesh`].
+///
+/// The triangulation is constructed incrementally by growing the convex hull (sweep-hull).
+/// Points are inserted by increasing distance to the circumcenter of a seed triangle, therefore,
+/// each new point lies outside of the current hull. After each insertion the Delaunay property
+/// is restored by Lawson flips.
+///
+/// The vertex with id ```i``` of the resulting mesh corresponds to ```points[i]```.
+/// Duplicated points (see [`DSPoint::nearly_equals`]) remain unconnected vertices.
+/// If all points are collinear, the mesh does not contain any face.
+///
+/// # Examples
+///
+/// ```
+/// use meshing::geometry::Point;
+/// use meshing::triangulator::triangulate;
+///
+/// let points = vec![
+///   Point { x: 0., y: 0. },
+///   Point { x: 1., y: 0. },
+///   Point { x: 1., y: 1. },
+///   Point { x: 0., y: 1. },
+/// ];
+/// let mesh = triangulate(&points);
+/// assert_eq!(mesh.iter_faces().count(), 2);
+/// ```
+pub fn triangulate(points: &[Point]) -> Mesh {
+  Triangulator::new(points).triangulate()
+}
+
+struct Triangulator<'a> {
+  points: &'a [Point],
+  mesh: Mesh,
+  center: Point,
+  hash: Vec<usize>,
+  hull_edges: Vec<usize>,
+}
+
+impl<'a> Triangulator<'a> {
+
+  fn new(points: &'a [Point]) -> Self {
+    let mut mesh = Mesh::empty();
+    for p in points.iter() {
+      mesh.create_vertex(p.clone());
+    }
+
+    let hash_size = (points.len() as f64).sqrt().ceil() as usize;
+    Triangulator {
+      points,
+      mesh,
+      center: Point {x: 0.0, y: 0.0},
+      hash: vec![EMPTY; hash_size.max(1)],
+      hull_edges: vec![EMPTY; points.len()],
+    }
+  }
+
+  fn triangulate(mut self) -> Mesh {
+    let (i0, i1, i2) = match self.find_seed_triangle() {
+      Some(seed) => seed,
+      None => return self.mesh,
+    };
+
+    self.center = self.points[i0].circumcenter(&self.points[i1], &self.points[i2]);
+    self.mesh.create_triangle(i0, i1, i2);
+    for halfedge in self.mesh.iter_face(self.mesh.boundary()).collect::<Vec<usize>>() {
+      self.update_hull(halfedge);
+    }
+
+    let mut ids: Vec<usize> = (0..self.points.len()).collect();
+    let dists: Vec<f64> = self.points.iter().map(|p| p.distance_sq(&self.center)).collect();
+    ids.sort_by(|&i, &j| dists[i].partial_cmp(&dists[j]).unwrap());
+
+    let mut last: Option<usize> = None;
+    for i in ids {
+      if i == i0 || i == i1 || i == i2 {
+        continue;
+      }
+
+      // skip near-duplicates
+      if last.is_some_and(|j| self.points[i].nearly_equals(&self.points[j])) {
+        continue;
+      }
+      last = Some(i);
+
+      if self.points[i].nearly_equals(&self.points[i0]) ||
+         self.points[i].nearly_equals(&self.points[i1]) ||
+         self.points[i].nearly_equals(&self.points[i2]) {
+        continue;
+      }
+
+      self.add_to_hull(i);
+    }
+
+    self.mesh
+  }
+
+  /// Inserts vertex ```v``` which is assumed to be outside of the current hull.
+  fn add_to_hull(&mut self, v: usize) {
+    let edge = match self.find_visible_edge(v) {
+      Some(edge) => edge,
+      // the point lies (numerically) on the hull
+      None => return,
+    };
+
+    let t2 = self.mesh.insert_vertex(edge, v);
+    let t1 = self.mesh.next(t2);
+    self.mesh.legalize(edge);
+
+    // walk forward along the hull and close all edges visible from v
+    let mut forward = t1;
+    loop {
+      let next = self.mesh.next(forward);
+      if !self.is_visible(next, v) {
+        break;
+      }
+      let end = self.mesh.vertex(forward);
+      forward = self.mesh.connect(forward);
+      self.hull_edges[end] = EMPTY;
+      self.mesh.legalize(next);
+    }
+
+    // walk backward along the hull and close all edges visible from v
+    let mut backward = t2;
+    loop {
+      let prev = self.mesh.prev(backward);
+      if !self.is_visible(prev, v) {
+        break;
+      }
+      let end = self.mesh.vertex(prev);
+      backward = self.mesh.connect(prev);
+      self.hull_edges[end] = EMPTY;
+      self.mesh.legalize(prev);
+    }
+
+    self.update_hull(forward);
+    self.update_hull(backward);
+  }
+
+  /// Registers the border ```halfedge``` as the hull edge starting at its start vertex.
+  fn update_hull(&mut self, halfedge: usize) {
+    let start = self.mesh.vertex(self.mesh.prev(halfedge));
+    self.hull_edges[start] = halfedge;
+    let key = self.hash_key(&self.points[start]);
+    self.hash[key] = start;
+  }
+
+  /// Returns **true** if and only if the border ```halfedge``` is visible from vertex ```v```.
+  fn is_visible(&self, halfedge: usize, v: usize) -> bool {
+    let u1 = self.mesh.point_of_edge(self.mesh.prev(halfedge));
+    let u2 = self.mesh.point_of_edge(halfedge);
+    u1.orient(u2, &self.points[v]) > 0.0
+  }
+
+  fn find_visible_edge(&self, v: usize) -> Option<usize> {
+    let key = self.hash_key(&self.points[v]);
+    let mut start = EMPTY;
+    for j in 0..self.hash.len() {
+      start = self.hash[(key + j) % self.hash.len()];
+      if start != EMPTY && self.hull_edges[start] != EMPTY {
+        break;
+      }
+    }
+
+    let start = self.mesh.prev(self.hull_edges[start]);
+    let mut edge = start;
+    while !self.is_visible(edge, v) {
+      edge = self.mesh.next(edge);
+      if edge == start {
+        return None;
+      }
+    }
+    Some(edge)
+  }
+
+  fn hash_key(&self, p: &Point) -> usize {
+    let angle = pseudo_angle(p.x - self.center.x, p.y - self.center.y);
+    (angle * self.hash.len() as f64).floor() as usize % self.hash.len()
+  }
+
+  /// Returns three indices of ```points``` forming a small triangle close to the center of all points in ccw order.
+  fn find_seed_triangle(&self) -> Option<(usize, usize, usize)> {
+    let center = calc_bbox_center(self.points);
+
+    let i0 = self.closest(&center, EMPTY)?;
+    let i1 = self.closest(&self.points[i0], i0)?;
+    let p0 = &self.points[i0];
+    let p1 = &self.points[i1];
+
+    let mut min_radius = f64::INFINITY;
+    let mut i2 = EMPTY;
+    for (i, p) in self.points.iter().enumerate() {
+      if i == i0 || i == i1 || p0.orient(p1, p) == 0.0 {
+        continue;
+      }
+      let r = p0.circumradius_sq(p1, p);
+      if r < min_radius {
+        i2 = i;
+        min_radius = r;
+      }
+    }
+
+    if i2 == EMPTY {
+      return None;
+    }
+
+    if p0.ccw(p1, &self.points[i2]) {
+      Some((i0, i1, i2))
+    } else {
+      Some((i0, i2, i1))
+    }
+  }
+
+  /// Returns the index of the point closest to ```p``` which is neither ```exclude``` nor a duplicate of ```p```.
+  fn closest(&self, p: &Point, exclude: usize) -> Option<usize> {
+    let mut min_dist = f64::INFINITY;
+    let mut k = None;
+    for (i, q) in self.points.iter().enumerate() {
+      if i == exclude || (exclude != EMPTY && p.nearly_equals(q)) {
+        continue;
+      }
+      let d = p.distance_sq(q);
+      if d < min_dist {
+        k = Some(i);
+        min_dist = d;
+      }
+    }
+    k
+  }
+}
+
+/// Monotonically increases with the real angle of (```dx```, ```dy```) but does not require expensive trigonometry.
+fn pseudo_angle(dx: f64, dy: f64) -> f64 {
+  let p = dx / (dx.abs() + dy.abs());
+  (if dy > 0.0 { 3.0 - p } else { 1.0 + p }) / 4.0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::seeded_points;
+
+  fn assert_delaunay(mesh: &Mesh) {
+    assert!(mesh.iter_edges().all(|halfedge| !mesh.is_illegal(halfedge)));
+  }
+
+  fn assert_ccw(mesh: &Mesh) {
+    for face in mesh.iter_faces() {
+      let points: Vec<&Point> = mesh.iter_face(face).map(|halfedge| mesh.point_of_edge(halfedge)).collect();
+      assert_eq!(points.len(), 3);
+      assert!(points[0].ccw(points[1], points[2]));
+    }
+  }
+
+  #[test]
+  fn test_square() {
+    let points = vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 1.0, y: 0.0},
+      Point {x: 1.0, y: 1.0},
+      Point {x: 0.0, y: 1.0},
+    ];
+    let mesh = triangulate(&points);
+    assert_eq!(mesh.iter_faces().count(), 2);
+    assert_eq!(mesh.iter_face(mesh.boundary()).count(), 4);
+    assert_eq!(mesh.iter_edges().count(), 10);
+    assert_ccw(&mesh);
+  }
+
+  #[test]
+  fn test_random_points() {
+    let points = seeded_points(500, 42, 100.0);
+    let mesh = triangulate(&points);
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+
+    // Euler: a triangulation of n points with h points on the hull consists of 2n - h - 2 triangles
+    assert_eq!(mesh.iter_faces().count(), 2 * points.len() - nhull - 2);
+    assert_eq!(mesh.number_of_vertices(), points.len());
+    assert!(mesh.validate());
+    assert_eq!(mesh.check_delaunay(), Ok(()));
+    assert_ccw(&mesh);
+    assert_delaunay(&mesh);
+  }
+
+  #[test]
+  fn test_grid() {
+    let mut points = Vec::new();
+    for i in 0..10 {
+      for j in 0..10 {
+        points.push(Point {x: i as f64, y: j as f64});
+      }
+    }
+    let mesh = triangulate(&points);
+    assert_eq!(mesh.iter_faces().count(), 2 * 9 * 9);
+    assert_ccw(&mesh);
+    assert_delaunay(&mesh);
+  }
+
+  #[test]
+  fn test_duplicates() {
+    let mut points = seeded_points(50, 42, 100.0);
+    points.push(points[3].clone());
+    points.push(points[17].clone());
+    let mesh = triangulate(&points);
+    let nhull = mesh.iter_face(mesh.boundary()).count();
+    assert_eq!(mesh.iter_faces().count(), 2 * (points.len() - 2) - nhull - 2);
+    assert_eq!(mesh.number_of_vertices(), points.len());
+    assert_ccw(&mesh);
+    assert_delaunay(&mesh);
+  }
+
+  #[test]
+  fn test_collinear() {
+    let points: Vec<Point> = (0..10).map(|i| Point {x: i as f64, y: 2.0 * i as f64}).collect();
+    let mesh = triangulate(&points);
+    assert_eq!(mesh.iter_faces().count(), 0);
+    assert_eq!(mesh.iter_edges().count(), 0);
+    assert_eq!(mesh.number_of_vertices(), points.len());
+  }
+
+  #[test]
+  fn test_collinear_and_one_point() {
+    let mut points: Vec<Point> = (0..10).map(|i| Point {x: i as f64, y: 0.0}).collect();
+    points.push(Point {x: 4.5, y: 1.0});
+    let mesh = triangulate(&points);
+    assert_eq!(mesh.iter_faces().count(), 9);
+    assert_ccw(&mesh);
+    assert_delaunay(&mesh);
+  }
+
+  #[test]
+  fn test_empty() {
+    assert_eq!(triangulate(&[]).iter_faces().count(), 0);
+    assert_eq!(triangulate(&[Point {x: 0.0, y: 0.0}]).iter_faces().count(), 0);
+  }
+}